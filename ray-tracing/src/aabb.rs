@@ -0,0 +1,102 @@
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+// Boîte englobante alignée sur les axes (Axis-Aligned Bounding Box)
+#[derive(Copy, Clone)]
+pub struct Aabb {
+    minimum: Point3,
+    maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(a: Point3, b: Point3) -> Aabb {
+        // On accepte les coins dans n'importe quel ordre
+        Aabb {
+            minimum: Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            maximum: Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn min(&self) -> Point3 {
+        self.minimum
+    }
+
+    pub fn max(&self) -> Point3 {
+        self.maximum
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    pub fn surrounding_box(a: Aabb, b: Aabb) -> Aabb {
+        Aabb {
+            minimum: Point3::new(
+                a.minimum.x().min(b.minimum.x()),
+                a.minimum.y().min(b.minimum.y()),
+                a.minimum.z().min(b.minimum.z()),
+            ),
+            maximum: Point3::new(
+                a.maximum.x().max(b.maximum.x()),
+                a.maximum.y().max(b.maximum.y()),
+                a.maximum.z().max(b.maximum.z()),
+            ),
+        }
+    }
+
+    // Evite les boîtes d'épaisseur nulle (triangle ou disque aligné sur un axe)
+    pub fn pad(&self) -> Aabb {
+        const DELTA: f64 = 1.0e-4;
+        let d = Vec3::new(
+            if self.maximum.x() - self.minimum.x() < DELTA { DELTA } else { 0.0 },
+            if self.maximum.y() - self.minimum.y() < DELTA { DELTA } else { 0.0 },
+            if self.maximum.z() - self.minimum.z() < DELTA { DELTA } else { 0.0 },
+        );
+        Aabb {
+            minimum: self.minimum - d,
+            maximum: self.maximum + d,
+        }
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.maximum - self.minimum;
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    pub fn longest_axis(&self) -> usize {
+        let d = self.maximum - self.minimum;
+        if d.x() > d.y() && d.x() > d.z() {
+            0
+        } else if d.y() > d.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.clip(r, t_min, t_max).is_some()
+    }
+
+    // Portion [t_min, t_max] du rayon à l'intérieur de la boîte, None si elle est vide
+    pub fn clip(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        // Même "slab method" que Cube : on réduit l'intervalle [t_min, t_max] axe par axe
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+        for a in 0..3 {
+            let inv_d = 1.0 / r.direction()[a];
+            let mut t0 = (self.minimum[a] - r.origin()[a]) * inv_d;
+            let mut t1 = (self.maximum[a] - r.origin()[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // f64::max / f64::min ignorent les NaN (rayon parallèle posé sur une face)
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
\ No newline at end of file
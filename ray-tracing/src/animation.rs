@@ -0,0 +1,32 @@
+use crate::camera::{Camera, CameraPose, CameraRig};
+use crate::keyframe::Track;
+
+/*
+Animation : une suite d'images numérotées, prises à `fps` images par seconde.
+L'image n (0 pour la première) est prise à l'instant n / fps : les instants des clés des objets
+(key <piste> time=) et de la caméra (key camera time=) sont donc en secondes.
+L'obturateur reste ouvert pendant une fraction `shutter` de la durée d'une image
+(0.5 correspond à l'obturateur à 180° du cinéma) : ce qui bouge vite est flou.
+Le monde n'est construit qu'une fois : seuls la caméra et l'instant changent d'une image à l'autre.
+*/
+pub struct Animation {
+    pub frames: usize,
+    pub fps: f64,
+    pub shutter: f64,
+    pub rig: CameraRig,
+    pub pose: CameraPose, // pose de la ligne `camera`, quand aucune clé ne l'anime
+    pub camera_track: Option<Track<CameraPose>>,
+}
+
+impl Animation {
+    pub fn time(&self, frame: usize) -> f64 {
+        frame as f64 / self.fps
+    }
+
+    // Caméra de l'image `frame`, avec l'obturateur ouvert à partir de son instant
+    pub fn camera(&self, frame: usize) -> Camera {
+        let time = self.time(frame);
+        let pose = self.camera_track.as_ref().map_or(self.pose, |track| track.at(time));
+        self.rig.camera(pose).with_shutter(time, time + self.shutter / self.fps)
+    }
+}
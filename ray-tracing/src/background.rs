@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::common::RenderRng;
+use crate::envmap::EnvironmentMap;
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+// Couleur renvoyée par un rayon qui ne touche aucun objet
+#[derive(Clone)]
+pub enum Background {
+    // Dégradé gris-bleu d'origine du projet
+    Sky,
+    // Couleur unie, noir pour une scène éclairée uniquement par ses lampes
+    Solid(Color),
+    // Interpolation linéaire entre le bas (direction -Y) et le haut (+Y)
+    Gradient { bottom: Color, top: Color },
+    // Image HDR tout autour de la scène, qui sert aussi de lampe échantillonnée
+    Environment(Arc<EnvironmentMap>),
+}
+
+impl Background {
+    pub fn color(&self, r: &Ray) -> Color {
+        let unit_direction = Vec3::unit_vector(r.direction());
+        match self {
+            Background::Sky => {
+                let t = 0.3 * (unit_direction.y() + 1.0);
+                (2.0 - t) * Color::new(0.4, 0.4, 0.4) + t * Color::new(0.2, 0.4, 0.6)
+            }
+            Background::Solid(color) => *color,
+            Background::Gradient { bottom, top } => {
+                let t = 0.5 * (unit_direction.y() + 1.0);
+                (1.0 - t) * *bottom + t * *top
+            }
+            Background::Environment(map) => map.radiance(unit_direction),
+        }
+    }
+
+    // Seule une carte d'environnement sait être visée : les fonds uniformes sont laissés aux rebonds
+    pub fn sample(&self, rng: &mut RenderRng) -> Option<(Vec3, f64)> {
+        match self {
+            Background::Environment(map) => map.sample(rng),
+            _ => None,
+        }
+    }
+
+    pub fn pdf_value(&self, direction: Vec3) -> f64 {
+        match self {
+            Background::Environment(map) => map.pdf_value(direction),
+            _ => 0.0,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use crate::aabb::Aabb;
+use crate::common::RenderRng;
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::ray::Ray;
+
+// Nombre maximum de primitives dans une feuille
+const MAX_LEAF_SIZE: usize = 4;
+// Nombre de "bacs" testés par axe pour l'heuristique SAH
+const SAH_BINS: usize = 12;
+
+/*
+Hiérarchie de volumes englobants (BVH) stockée à plat dans un Vec.
+Elle ne connaît que les boîtes des primitives et renvoie des indices :
+l'appelant fournit la fonction d'intersection d'une primitive.
+Cela permet de l'utiliser aussi bien pour la scène (Box<dyn Hittable>) que pour des
+primitives qui ne sont pas des objets à part entière.
+*/
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>,
+}
+
+pub struct BvhNode {
+    bbox: Aabb,
+    // Feuille : primitives indices[start..start + count]
+    // Noeud interne (count == 0) : enfant gauche = noeud suivant, enfant droit = `start`
+    start: usize,
+    count: usize,
+    axis: usize,
+}
+
+struct BuildItem {
+    bbox: Aabb,
+    centroid_bbox: Aabb,
+}
+
+impl Bvh {
+    pub fn new(bounds: &[Aabb]) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: Vec::with_capacity(2 * bounds.len()),
+            indices: (0..bounds.len()).collect(),
+        };
+        if !bounds.is_empty() {
+            bvh.build(bounds, 0, bounds.len());
+        }
+        bvh
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bbox)
+    }
+
+    fn range_bounds(&self, bounds: &[Aabb], start: usize, end: usize) -> BuildItem {
+        let first = bounds[self.indices[start]];
+        let mut bbox = first;
+        let mut centroid_bbox = Aabb::new(first.centroid(), first.centroid());
+        for &i in &self.indices[start + 1..end] {
+            bbox = Aabb::surrounding_box(bbox, bounds[i]);
+            let c = bounds[i].centroid();
+            centroid_bbox = Aabb::surrounding_box(centroid_bbox, Aabb::new(c, c));
+        }
+        BuildItem { bbox, centroid_bbox }
+    }
+
+    fn build(&mut self, bounds: &[Aabb], start: usize, end: usize) -> usize {
+        let item = self.range_bounds(bounds, start, end);
+        let node_index = self.nodes.len();
+        let count = end - start;
+        self.nodes.push(BvhNode {
+            bbox: item.bbox,
+            start,
+            count,
+            axis: 0,
+        });
+        if count <= MAX_LEAF_SIZE {
+            return node_index;
+        }
+
+        let axis = item.centroid_bbox.longest_axis();
+        let c_min = item.centroid_bbox.min()[axis];
+        let c_max = item.centroid_bbox.max()[axis];
+        // Toutes les primitives au même endroit : impossible de les séparer
+        if c_max - c_min <= 0.0 {
+            return node_index;
+        }
+
+        let mid = match self.sah_split(bounds, start, end, axis, c_min, c_max, item.bbox) {
+            Some(mid) => mid,
+            None => {
+                // Repli : coupe à la médiane
+                let mid = start + count / 2;
+                self.indices[start..end].select_nth_unstable_by(count / 2, |&a, &b| {
+                    bounds[a].centroid()[axis].total_cmp(&bounds[b].centroid()[axis])
+                });
+                mid
+            }
+        };
+
+        self.build(bounds, start, mid);
+        let right = self.build(bounds, mid, end);
+        let node = &mut self.nodes[node_index];
+        node.start = right;
+        node.count = 0;
+        node.axis = axis;
+        node_index
+    }
+
+    // Coupe par "Surface Area Heuristic" : renvoie la position de coupe dans indices,
+    // ou None si garder une feuille coûte moins cher que n'importe quelle coupe
+    #[allow(clippy::too_many_arguments)]
+    fn sah_split(
+        &mut self,
+        bounds: &[Aabb],
+        start: usize,
+        end: usize,
+        axis: usize,
+        c_min: f64,
+        c_max: f64,
+        node_box: Aabb,
+    ) -> Option<usize> {
+        let bin_of = |b: &Aabb| {
+            let k = (SAH_BINS as f64 * (b.centroid()[axis] - c_min) / (c_max - c_min)) as usize;
+            k.min(SAH_BINS - 1)
+        };
+
+        let mut bin_counts = [0usize; SAH_BINS];
+        let mut bin_boxes: [Option<Aabb>; SAH_BINS] = [None; SAH_BINS];
+        for &i in &self.indices[start..end] {
+            let k = bin_of(&bounds[i]);
+            bin_counts[k] += 1;
+            bin_boxes[k] = Some(match bin_boxes[k] {
+                Some(b) => Aabb::surrounding_box(b, bounds[i]),
+                None => bounds[i],
+            });
+        }
+
+        // Coût de chaque coupe entre le bac k et k + 1
+        let mut best_cost = f64::INFINITY;
+        let mut best_split = 0;
+        for split in 0..SAH_BINS - 1 {
+            let (mut left_box, mut right_box) = (None, None);
+            let (mut left_count, mut right_count) = (0, 0);
+            for k in 0..SAH_BINS {
+                let Some(b) = bin_boxes[k] else { continue };
+                if k <= split {
+                    left_count += bin_counts[k];
+                    left_box = Some(left_box.map_or(b, |l| Aabb::surrounding_box(l, b)));
+                } else {
+                    right_count += bin_counts[k];
+                    right_box = Some(right_box.map_or(b, |r| Aabb::surrounding_box(r, b)));
+                }
+            }
+            let (Some(l), Some(r)) = (left_box, right_box) else { continue };
+            let cost = left_count as f64 * l.surface_area() + right_count as f64 * r.surface_area();
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        let count = end - start;
+        let leaf_cost = count as f64 * node_box.surface_area();
+        if best_cost == f64::INFINITY || (count <= 2 * MAX_LEAF_SIZE && best_cost >= leaf_cost) {
+            return None;
+        }
+
+        // Partition des indices : bacs <= best_split à gauche
+        let slice = &mut self.indices[start..end];
+        let mut mid = 0;
+        for i in 0..slice.len() {
+            if bin_of(&bounds[slice[i]]) <= best_split {
+                slice.swap(i, mid);
+                mid += 1;
+            }
+        }
+        if mid == 0 || mid == count {
+            return None;
+        }
+        Some(start + mid)
+    }
+
+    // Parcours de l'arbre : `hit_primitive(i, t_max, rec)` teste la primitive i
+    pub fn hit<F>(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, mut hit_primitive: F) -> bool
+    where
+        F: FnMut(usize, f64, &mut HitRecord) -> bool,
+    {
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let mut hit_anything = false;
+        let mut closest_so_far = t_max;
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bbox.hit(r, t_min, closest_so_far) {
+                continue;
+            }
+            if node.count > 0 {
+                for &i in &self.indices[node.start..node.start + node.count] {
+                    if hit_primitive(i, closest_so_far, rec) {
+                        hit_anything = true;
+                        closest_so_far = rec.t;
+                    }
+                }
+            } else if r.direction()[node.axis] < 0.0 {
+                // On visite d'abord l'enfant le plus proche du rayon
+                stack.push(node_index + 1);
+                stack.push(node.start);
+            } else {
+                stack.push(node.start);
+                stack.push(node_index + 1);
+            }
+        }
+
+        hit_anything
+    }
+
+    // Parcours sans ordre : `visit(i)` reçoit chaque primitive dont la boîte croise le rayon
+    // entre t_min et t_max, tant qu'elle renvoie true
+    pub fn visit<F>(&self, r: &Ray, t_min: f64, t_max: f64, mut visit: F)
+    where
+        F: FnMut(usize) -> bool,
+    {
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bbox.hit(r, t_min, t_max) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node_index + 1);
+                stack.push(node.start);
+                continue;
+            }
+            for &i in &self.indices[node.start..node.start + node.count] {
+                if !visit(i) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// Scène accélérée : les objets bornés vont dans la BVH, les objets infinis (Plane) sont testés à part
+pub struct BvhTree {
+    objects: Vec<Box<dyn Hittable>>,
+    bvh: Bvh,
+    unbounded: HittableList,
+}
+
+impl BvhTree {
+    pub fn new(list: HittableList) -> BvhTree {
+        let mut objects = Vec::new();
+        let mut bounds = Vec::new();
+        let mut unbounded = HittableList::new();
+        for object in list.into_objects() {
+            match object.bounding_box() {
+                Some(bbox) => {
+                    bounds.push(bbox.pad());
+                    objects.push(object);
+                }
+                None => unbounded.add(object),
+            }
+        }
+
+        BvhTree {
+            bvh: Bvh::new(&bounds),
+            objects,
+            unbounded,
+        }
+    }
+
+    // Intersection la plus proche : objets infinis, puis ceux de la BVH devant la meilleure trouvée
+    fn closest<F>(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, mut hit_object: F) -> bool
+    where
+        F: FnMut(&dyn Hittable, f64, &mut HitRecord) -> bool,
+    {
+        let mut temp_rec = HitRecord::new();
+        let mut hit_anything = false;
+        let mut closest_so_far = t_max;
+
+        if hit_object(&self.unbounded, closest_so_far, &mut temp_rec) {
+            hit_anything = true;
+            closest_so_far = temp_rec.t;
+            *rec = temp_rec.clone();
+        }
+
+        let objects = &self.objects;
+        if self.bvh.hit(r, t_min, closest_so_far, &mut temp_rec, |i, t_max, rec| {
+            hit_object(objects[i].as_ref(), t_max, rec)
+        }) {
+            hit_anything = true;
+            *rec = temp_rec;
+        }
+
+        hit_anything
+    }
+}
+
+impl Hittable for BvhTree {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.closest(r, t_min, t_max, rec, |object, t_max, rec| object.hit(r, t_min, t_max, rec))
+    }
+
+    fn hit_sampled(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, rng: &mut RenderRng) -> bool {
+        self.closest(r, t_min, t_max, rec, |object, t_max, rec| object.hit_sampled(r, t_min, t_max, rec, rng))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.unbounded.is_empty() {
+            self.bvh.bounding_box()
+        } else {
+            None
+        }
+    }
+
+    // Pas besoin du plus proche : on multiplie tout ce qui est traversé, et une surface opaque arrête tout
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64, rng: &mut RenderRng) -> f64 {
+        let mut total = self.unbounded.transmittance(r, t_min, t_max, rng);
+        let objects = &self.objects;
+        if total > 0.0 {
+            self.bvh.visit(r, t_min, t_max, |i| {
+                total *= objects[i].transmittance(r, t_min, t_max, rng);
+                total > 0.0
+            });
+        }
+        total
+    }
+}
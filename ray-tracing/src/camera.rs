@@ -1,12 +1,91 @@
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
-use crate::common;
+use crate::common::{self, RenderRng};
+use crate::keyframe::Keyable;
+
+// Répartition des angles sur l'image d'un objectif fisheye
+#[derive(Copy, Clone)]
+pub enum FisheyeMapping {
+    Equidistant, // r proportionnel à l'angle
+    Equisolid,   // r proportionnel à sin(angle / 2), conserve les surfaces
+}
+
+#[derive(Copy, Clone)]
+pub enum Projection {
+    Perspective { vfov: f64 },    // Vertical field-of-view in degrees
+    Orthographic { height: f64 }, // Hauteur visible, en unités de la scène
+    Fisheye { fov: f64, mapping: FisheyeMapping }, // fov : angle du cercle inscrit dans la hauteur
+    Equirectangular,              // 360° x 180°, pour la VR et les cartes d'environnement
+}
+
+// Ce qui bouge d'une image à l'autre d'une animation : position, point visé et champ.
+// `fov` est l'angle vertical (perspective), l'angle du cercle (fisheye) ou la hauteur visible
+// (orthographique) ; le panorama équirectangulaire l'ignore
+#[derive(Copy, Clone)]
+pub struct CameraPose {
+    pub lookfrom: Point3,
+    pub lookat: Point3,
+    pub fov: f64,
+}
+
+impl Keyable for CameraPose {
+    fn weighted(values: &[(f64, CameraPose)]) -> CameraPose {
+        let mut out = CameraPose { lookfrom: Vec3::default(), lookat: Vec3::default(), fov: 0.0 };
+        for &(w, pose) in values {
+            out.lookfrom = out.lookfrom + w * pose.lookfrom;
+            out.lookat = out.lookat + w * pose.lookat;
+            out.fov += w * pose.fov;
+        }
+        out
+    }
+}
+
+// Réglages fixes de la caméra, complétés par une pose pour construire chaque image
+#[derive(Copy, Clone)]
+pub struct CameraRig {
+    pub vup: Vec3,
+    pub projection: Projection,
+    pub aspect_ratio: f64,
+    pub aperture: f64,
+    pub focus_dist: Option<f64>, // None : netteté sur le point visé, qui peut bouger
+}
+
+impl CameraRig {
+    // Le champ de la projection vient de la pose
+    pub fn fov(&self) -> f64 {
+        match self.projection {
+            Projection::Perspective { vfov } => vfov,
+            Projection::Orthographic { height } => height,
+            Projection::Fisheye { fov, .. } => fov,
+            Projection::Equirectangular => 0.0,
+        }
+    }
+
+    pub fn camera(&self, pose: CameraPose) -> Camera {
+        let projection = match self.projection {
+            Projection::Perspective { .. } => Projection::Perspective { vfov: pose.fov },
+            Projection::Orthographic { .. } => Projection::Orthographic { height: pose.fov },
+            Projection::Fisheye { mapping, .. } => Projection::Fisheye { fov: pose.fov, mapping },
+            Projection::Equirectangular => Projection::Equirectangular,
+        };
+        let focus_dist = self.focus_dist.unwrap_or_else(|| (pose.lookfrom - pose.lookat).vec_length());
+        Camera::new(pose.lookfrom, pose.lookat, self.vup, projection, self.aspect_ratio, self.aperture, focus_dist)
+    }
+}
  
 pub struct Camera {
     origin: Point3,
     lower_left_corner: Point3,
     horizontal: Vec3,
     vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+    lens_radius: f64,
+    aspect_ratio: f64,
+    projection: Projection,
+    time0: f64, // Ouverture et fermeture de l'obturateur
+    time1: f64,
 }
  
 impl Camera {
@@ -14,35 +93,132 @@ impl Camera {
         lookfrom: Point3,
         lookat: Point3,
         vup: Vec3,
-        vfov: f64, // Vertical field-of-view in degrees
+        projection: Projection,
         aspect_ratio: f64,
+        aperture: f64,   // Diamètre de l'objectif, 0 pour une caméra sténopé (tout est net)
+        focus_dist: f64, // Distance du plan de netteté
     ) -> Camera {
-        let theta = common::degrees_to_radians(vfov);
-        let h = f64::tan(theta / 2.0);
-        let viewport_height = 2.0 * h;
-        let viewport_width = aspect_ratio * viewport_height;
- 
         let w = Vec3::unit_vector(lookfrom - lookat);
         let u = Vec3::unit_vector(Vec3::cross(vup, w));
         let v = Vec3::cross(w, u);
- 
         let origin = lookfrom;
-        let horizontal = viewport_width * u;
-        let vertical = viewport_height * v;
-        let lower_left_corner = origin - horizontal / 2.0 - vertical / 2.0 - w;
+
+        // Le viewport est placé sur le plan de netteté (perspective) ou passe par la caméra (orthographique)
+        let (horizontal, vertical, lower_left_corner) = match projection {
+            Projection::Orthographic { height } => {
+                let horizontal = aspect_ratio * height * u;
+                let vertical = height * v;
+                (horizontal, vertical, origin - horizontal / 2.0 - vertical / 2.0)
+            }
+            Projection::Perspective { vfov } => {
+                let theta = common::degrees_to_radians(vfov);
+                let h = f64::tan(theta / 2.0);
+                let viewport_height = 2.0 * h;
+                let viewport_width = aspect_ratio * viewport_height;
+                let horizontal = focus_dist * viewport_width * u;
+                let vertical = focus_dist * viewport_height * v;
+                (horizontal, vertical, origin - horizontal / 2.0 - vertical / 2.0 - focus_dist * w)
+            }
+            // Les projections angulaires calculent directement une direction
+            Projection::Fisheye { .. } | Projection::Equirectangular => {
+                (Vec3::default(), Vec3::default(), origin)
+            }
+        };
  
         Camera {
             origin,
             lower_left_corner,
             horizontal,
             vertical,
+            u,
+            v,
+            w,
+            // La profondeur de champ n'a de sens que pour la perspective
+            lens_radius: match projection {
+                Projection::Perspective { .. } => aperture / 2.0,
+                _ => 0.0,
+            },
+            aspect_ratio,
+            projection,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
+
+    // Obturateur ouvert de `open` à `close` : chaque rayon part à un instant tiré dans cet intervalle,
+    // les objets en mouvement laissent une traînée floue
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Camera {
+        self.time0 = open;
+        self.time1 = close;
+        self
+    }
+
+    // Direction dans le repère de la caméra : x vers la droite, y vers le haut, -z devant
+    fn local_direction(&self, x: f64, y: f64, z: f64) -> Vec3 {
+        x * self.u + y * self.v + z * self.w
+    }
  
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        Ray::new(
-            self.origin,
-            self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin,
-        )
+    // s et t dans [0, 1] : de gauche à droite et de bas en haut de l'image
+    pub fn get_ray(&self, s: f64, t: f64, rng: &mut RenderRng) -> Ray {
+        // Obturateur instantané : on ne tire rien, le rendu reste celui d'une image fixe
+        let time = if self.time1 > self.time0 {
+            common::random_double_range(rng, self.time0, self.time1)
+        } else {
+            self.time0
+        };
+        match self.projection {
+            Projection::Perspective { .. } => {
+                // Les rayons partent d'un point de l'objectif : seul le plan de netteté reste net.
+                // Sans ouverture on ne tire rien, pour garder exactement le rendu sténopé
+                let offset = if self.lens_radius > 0.0 {
+                    let rd = self.lens_radius * Vec3::random_in_unit_disk(rng);
+                    rd.x() * self.u + rd.y() * self.v
+                } else {
+                    Vec3::default()
+                };
+                Ray::new(
+                    self.origin + offset,
+                    self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
+                    time,
+                )
+            }
+            Projection::Orthographic { .. } => Ray::new(
+                self.lower_left_corner + s * self.horizontal + t * self.vertical,
+                -self.w,
+                time,
+            ),
+            Projection::Fisheye { fov, mapping } => {
+                // Cercle de rayon 1 inscrit dans la hauteur, les coins vont au-delà (plein cadre)
+                let x = (2.0 * s - 1.0) * self.aspect_ratio;
+                let y = 2.0 * t - 1.0;
+                let r = f64::sqrt(x * x + y * y);
+                let half_fov = common::degrees_to_radians(fov) / 2.0;
+                let theta = match mapping {
+                    FisheyeMapping::Equidistant => r * half_fov,
+                    FisheyeMapping::Equisolid => {
+                        2.0 * f64::asin(f64::min(r * f64::sin(half_fov / 2.0), 1.0))
+                    }
+                }
+                .min(common::PI);
+                let phi = f64::atan2(y, x);
+                let dir = self.local_direction(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    -theta.cos(),
+                );
+                Ray::new(self.origin, dir, time)
+            }
+            Projection::Equirectangular => {
+                // Longitude sur la largeur, latitude sur la hauteur, le centre de l'image regarde lookat
+                let phi = (s - 0.5) * 2.0 * common::PI;
+                let lambda = (t - 0.5) * common::PI;
+                let dir = self.local_direction(
+                    lambda.cos() * phi.sin(),
+                    lambda.sin(),
+                    -lambda.cos() * phi.cos(),
+                );
+                Ray::new(self.origin, dir, time)
+            }
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,241 @@
+use std::error::Error;
+use std::fmt;
+use std::path::{self, Component, Path, PathBuf};
+use std::str::FromStr;
+use std::thread;
+
+use crate::constants::*;
+use crate::exr::{ExrCompression, ExrPixel};
+use crate::image::ImageFormat;
+use crate::scene::Overrides;
+use crate::tonemap::ToneMap;
+
+pub const USAGE: &str = "\
+Usage: ray-tracing [OPTIONS] [SCENE]
+
+Arguments:
+  [SCENE]                 Scene file to render (default: scenes/demo.scene)
+
+Options:
+  -o, --output <PATH>     Output image (default: full_obj_pov1.ppm)
+      --frames <DIR>      Render the scene's animation to DIR/0001.png, DIR/0002.png, ...
+                          Frames already in DIR are skipped: an interrupted run resumes
+  -f, --format <FORMAT>   p3 (ASCII PPM), p6 (binary PPM), png, pfm or exr
+                          (default: guessed from the output extension, png for --frames)
+      --exr-pixel <TYPE>  half (default) or float
+      --exr-compression <C>
+                          zip (default) or none
+  -W, --width <PIXELS>    Image width
+  -H, --height <PIXELS>   Image height (default: width / aspect)
+  -a, --aspect <RATIO>    Aspect ratio, e.g. 16/9 or 1.5
+  -s, --samples <N>       Samples per pixel
+  -d, --depth <N>         Maximum ray bounces
+  -e, --exposure <EV>     Exposure in stops: +1 doubles the brightness, -1 halves it
+  -t, --tonemap <NAME>    clamp, reinhard, extended, aces or agx
+      --seed <N>          Random seed; the same seed always gives the same image
+                          (default: a random seed, printed at startup; with --frames,
+                          derived from DIR so that a resumed animation keeps its noise)
+  -j, --threads <N>       Worker threads (default: all cores)
+  -h, --help              Print this help
+
+Width, height, aspect, samples, depth, exposure and tonemap override the scene's `render` line,
+which itself overrides the defaults of constants.rs.
+PFM and OpenEXR store the linear radiance: exposure and tonemap do not apply.";
+
+#[derive(Debug)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for CliError {}
+
+pub struct Options {
+    pub scene: PathBuf,
+    pub output: PathBuf,
+    pub frames: Option<PathBuf>, // dossier des images d'une animation
+    pub format: ImageFormat,
+    pub overrides: Overrides,
+    pub seed: u64,
+    pub threads: usize,
+}
+
+// Résultat du parsing : soit des options, soit une demande d'aide
+pub enum Command {
+    Render(Options),
+    Help,
+}
+
+fn value<T: FromStr>(flag: &str, args: &mut impl Iterator<Item = String>) -> Result<T, CliError> {
+    let text = args
+        .next()
+        .ok_or_else(|| CliError(format!("missing value for '{}'", flag)))?;
+    text.parse()
+        .map_err(|_| CliError(format!("invalid value '{}' for '{}'", text, flag)))
+}
+
+fn positive<T: FromStr + PartialOrd + Default>(
+    flag: &str,
+    args: &mut impl Iterator<Item = String>,
+) -> Result<T, CliError> {
+    let v: T = value(flag, args)?;
+    if v <= T::default() {
+        return Err(CliError(format!("'{}' must be greater than zero", flag)));
+    }
+    Ok(v)
+}
+
+fn aspect(args: &mut impl Iterator<Item = String>) -> Result<f64, CliError> {
+    let text: String = value("--aspect", args)?;
+    let ratio = match text.split_once('/') {
+        Some((w, h)) => w.parse::<f64>().ok().zip(h.parse::<f64>().ok()).map(|(w, h)| w / h),
+        None => text.parse().ok(),
+    };
+    match ratio {
+        Some(r) if r.is_finite() && r > 0.0 => Ok(r),
+        _ => Err(CliError(format!("invalid value '{}' for '--aspect'", text))),
+    }
+}
+
+// Graine tirée du dossier d'une animation (FNV-1a) : la même à chaque lancement,
+// les images rendues après une reprise ont donc le même bruit que les premières.
+// Le chemin est rendu absolu et haché composant par composant, pour que out, out/ et ./out
+// donnent la même graine
+fn path_seed(dir: &Path) -> u64 {
+    let dir = path::absolute(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let mut hash = 0xCBF2_9CE4_8422_2325u64;
+    for component in dir.components().filter(|c| *c != Component::CurDir) {
+        for &b in component.as_os_str().as_encoded_bytes().iter().chain(b"/") {
+            hash = (hash ^ b as u64).wrapping_mul(0x0100_0000_01B3);
+        }
+    }
+    hash
+}
+
+// `args` ne contient pas le nom du programme
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, CliError> {
+    let mut args = args.into_iter();
+    let mut scene = None;
+    let mut output = None;
+    let mut frames = None;
+    let mut format = None;
+    let mut exr_pixel = None;
+    let mut exr_compression = None;
+    let mut overrides = Overrides::default();
+    let mut seed = None;
+    let mut threads = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-o" | "--output" => output = Some(value::<PathBuf>(&arg, &mut args)?),
+            "--frames" => frames = Some(value::<PathBuf>(&arg, &mut args)?),
+            "-f" | "--format" => {
+                let name: String = value(&arg, &mut args)?;
+                format = Some(
+                    ImageFormat::from_name(&name)
+                        .ok_or_else(|| CliError(format!("unknown image format '{}'", name)))?,
+                );
+            }
+            "--exr-pixel" => {
+                let name: String = value(&arg, &mut args)?;
+                exr_pixel = Some(
+                    ExrPixel::from_name(&name)
+                        .ok_or_else(|| CliError(format!("unknown EXR pixel type '{}'", name)))?,
+                );
+            }
+            "--exr-compression" => {
+                let name: String = value(&arg, &mut args)?;
+                exr_compression = Some(
+                    ExrCompression::from_name(&name)
+                        .ok_or_else(|| CliError(format!("unknown EXR compression '{}'", name)))?,
+                );
+            }
+            "-W" | "--width" => overrides.width = Some(positive(&arg, &mut args)?),
+            "-H" | "--height" => overrides.height = Some(positive(&arg, &mut args)?),
+            "-a" | "--aspect" => overrides.aspect = Some(aspect(&mut args)?),
+            "-s" | "--samples" => overrides.samples = Some(positive(&arg, &mut args)?),
+            "-d" | "--depth" => overrides.depth = Some(positive(&arg, &mut args)?),
+            "-e" | "--exposure" => {
+                let ev: f64 = value(&arg, &mut args)?;
+                if !ev.is_finite() {
+                    return Err(CliError(format!("invalid value '{}' for '{}'", ev, arg)));
+                }
+                overrides.exposure = Some(ev);
+            }
+            "-t" | "--tonemap" => {
+                let name: String = value(&arg, &mut args)?;
+                // Le point blanc de `extended` est repris de la scène
+                overrides.tone_map = Some(
+                    ToneMap::from_name(&name, WHITE_POINT)
+                        .ok_or_else(|| CliError(format!("unknown tone mapping operator '{}'", name)))?,
+                );
+            }
+            "--seed" => seed = Some(value(&arg, &mut args)?),
+            "-j" | "--threads" => threads = Some(positive(&arg, &mut args)?),
+            _ if arg.starts_with('-') => return Err(CliError(format!("unknown option '{}'", arg))),
+            _ if scene.is_none() => scene = Some(PathBuf::from(arg)),
+            _ => return Err(CliError(format!("unexpected argument '{}'", arg))),
+        }
+    }
+
+    if output.is_some() && frames.is_some() {
+        return Err(CliError("'--output' and '--frames' cannot be used together".to_string()));
+    }
+    let output = output.unwrap_or_else(|| PathBuf::from(OUTPUT_FILE));
+    let format = match format {
+        Some(format) => format,
+        None if frames.is_some() => ImageFormat::Png,
+        None => ImageFormat::from_path(&output).ok_or_else(|| {
+            CliError(format!(
+                "cannot guess the image format of '{}', use --format",
+                output.display()
+            ))
+        })?,
+    };
+    let format = match format {
+        ImageFormat::Exr { pixel, compression } => ImageFormat::Exr {
+            pixel: exr_pixel.unwrap_or(pixel),
+            compression: exr_compression.unwrap_or(compression),
+        },
+        _ if exr_pixel.is_some() || exr_compression.is_some() => {
+            return Err(CliError("'--exr-pixel' and '--exr-compression' need an EXR output".to_string()))
+        }
+        other => other,
+    };
+
+    let seed = match (seed, &frames) {
+        (Some(seed), _) => seed,
+        (None, Some(dir)) => path_seed(dir),
+        (None, None) => rand::random(),
+    };
+    Ok(Command::Render(Options {
+        scene: scene.unwrap_or_else(|| PathBuf::from(SCENE_FILE)),
+        output,
+        frames,
+        format,
+        overrides,
+        seed,
+        threads: threads.unwrap_or_else(|| thread::available_parallelism().map(|n| n.get()).unwrap_or(1)),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_seed_ignores_the_spelling_of_the_directory() {
+        let seed = path_seed(Path::new("images/anim"));
+        let cwd = std::env::current_dir().unwrap();
+        for same in ["images/anim/", "./images/anim", "images/./anim", "./images/anim/."] {
+            assert_eq!(path_seed(Path::new(same)), seed, "{}", same);
+        }
+        assert_eq!(path_seed(&cwd.join("images/anim")), seed);
+        assert_ne!(path_seed(Path::new("images/anim2")), seed);
+        assert_ne!(path_seed(Path::new("image/sanim")), seed);
+    }
+}
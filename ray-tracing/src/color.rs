@@ -1,30 +1,37 @@
-
-use std::io::Write;
- 
-use crate::common;
 use crate::vec3::Vec3;
  
 // Type alias
 pub type Color = Vec3;
+
+// Courbe de transfert sRGB (OETF) : valeur linéaire [0, 1] vers valeur encodée pour l'écran
+fn srgb_encode(x: f64) -> f64 {
+    if x <= 0.0031308 {
+        12.92 * x
+    } else {
+        1.055 * x.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// Inverse : valeur encodée (octet d'une image / 255) vers valeur linéaire
+fn srgb_decode(x: f64) -> f64 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
  
-pub fn write_color(out: &mut impl Write, pixel_color: Color, samples_per_pixel: i32) {
-    let mut r = pixel_color.x();
-    let mut g = pixel_color.y();
-    let mut b = pixel_color.z();
- 
-    // Divide the color by the number of samples
-    let scale = 1.0 / samples_per_pixel as f64;
-    r = f64::sqrt(scale * r);
-    g = f64::sqrt(scale * g);
-    b = f64::sqrt(scale * b);
- 
-    // Write the translated [0, 255] value of each color component
-    writeln!(
-        out,
-        "{} {} {}",
-        (256.0 * common::clamp(r, 0.0, 0.999)) as i32,
-        (256.0 * common::clamp(g, 0.0, 0.999)) as i32,
-        (256.0 * common::clamp(b, 0.0, 0.999)) as i32,
+// Convertit une couleur d'affichage linéaire (déjà exposée et tone mappée, dans [0, 1]) en octets sRGB
+pub fn to_rgb8(display: Color) -> [u8; 3] {
+    let byte = |x: f64| (255.0 * srgb_encode(x.clamp(0.0, 1.0)) + 0.5) as u8;
+    [byte(display.x()), byte(display.y()), byte(display.z())]
+}
+
+// Les couleurs lues dans une image (textures) redeviennent linéaires
+pub fn to_linear(encoded: Color) -> Color {
+    Color::new(
+        srgb_decode(encoded.x()),
+        srgb_decode(encoded.y()),
+        srgb_decode(encoded.z()),
     )
-    .expect("writing color");
-}
\ No newline at end of file
+}
@@ -1,26 +1,35 @@
+use rand::rngs::StdRng;
 pub use rand::Rng;
+use rand::SeedableRng;
 pub use std::f64::consts::PI;
-pub use std::f64::INFINITY;
+pub const INFINITY: f64 = f64::INFINITY;
+
+// Générateur du rendu : jamais global, il est passé explicitement à tout ce qui tire au hasard
+pub type RenderRng = StdRng;
  
 pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
 
-pub fn random_double() -> f64 {
+// Mélangeur SplitMix64 : deux entrées proches donnent des sorties sans rapport
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Chaque pixel a sa propre suite de tirages, dérivée de la graine globale et de sa position :
+// l'image ne dépend ni du nombre de threads ni de l'ordre de calcul des tuiles
+pub fn pixel_rng(seed: u64, x: usize, y: usize) -> RenderRng {
+    StdRng::seed_from_u64(mix(seed ^ mix((y as u64) << 32 | x as u64)))
+}
+
+pub fn random_double(rng: &mut RenderRng) -> f64 {
     // between 0-1
-    rand::thread_rng().gen()
+    rng.gen()
 }
  
-pub fn random_double_range(min: f64, max: f64) -> f64 {
-    min + (max - min) * random_double()
+pub fn random_double_range(rng: &mut RenderRng, min: f64, max: f64) -> f64 {
+    min + (max - min) * random_double(rng)
 }
-
-pub fn clamp(x: f64, min: f64, max: f64) -> f64 {
-    if x < min {
-        return min;
-    }
-    if x > max {
-        return max;
-    }
-    x
-}
\ No newline at end of file
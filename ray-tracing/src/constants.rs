@@ -1,5 +1,9 @@
 pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
 pub const IMAGE_WIDTH: i32 = 1200;
-pub const IMAGE_HEIGHT: i32 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as i32;
 pub const SAMPLES_PER_PIXEL: i32 = 100;
-pub const MAX_DEPTH: i32 = 50;
\ No newline at end of file
+pub const MAX_DEPTH: i32 = 50;
+pub const MAX_IMAGE_SIDE: usize = 1 << 16; // pixels, au-delà le framebuffer ne tient pas en mémoire
+pub const EXPOSURE: f64 = 0.0; // EV
+pub const WHITE_POINT: f64 = 4.0; // luminance rendue blanche par le tone mapping "extended"
+pub const SCENE_FILE: &str = "scenes/demo.scene";
+pub const OUTPUT_FILE: &str = "full_obj_pov1.ppm";
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{HitRecord, Hittable, Interval};
+use crate::ray::Ray;
+use crate::vec3::Point3;
+
+/*
+Géométrie de construction (CSG) : deux solides combinés par une opération booléenne.
+Chaque solide donne les portions du rayon qui sont à l'intérieur ; on parcourt leurs bords
+dans l'ordre et on garde ceux où l'état "dans le résultat" change.
+Chaque surface garde son matériau : creuser une sphère dans un cube montre la matière de la sphère.
+*/
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CsgOp {
+    Union,        // A ou B
+    Intersection, // A et B
+    Difference,   // A moins B
+}
+
+impl CsgOp {
+    pub fn from_name(name: &str) -> Option<CsgOp> {
+        match name {
+            "union" => Some(CsgOp::Union),
+            "intersection" => Some(CsgOp::Intersection),
+            "difference" => Some(CsgOp::Difference),
+            _ => None,
+        }
+    }
+
+    fn inside(self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOp::Union => in_a || in_b,
+            CsgOp::Intersection => in_a && in_b,
+            CsgOp::Difference => in_a && !in_b,
+        }
+    }
+}
+
+pub struct Csg {
+    op: CsgOp,
+    a: Arc<dyn Hittable>,
+    b: Arc<dyn Hittable>,
+    bbox: Option<Aabb>,
+}
+
+impl Csg {
+    // None si l'un des deux objets n'est pas un solide fermé
+    pub fn new(op: CsgOp, a: Arc<dyn Hittable>, b: Arc<dyn Hittable>) -> Option<Csg> {
+        if !a.is_solid() || !b.is_solid() {
+            return None;
+        }
+        let (box_a, box_b) = (a.bounding_box()?, b.bounding_box()?);
+        let bbox = match op {
+            CsgOp::Union => Some(Aabb::surrounding_box(box_a, box_b)),
+            CsgOp::Intersection => {
+                let min = Point3::new(
+                    box_a.min().x().max(box_b.min().x()),
+                    box_a.min().y().max(box_b.min().y()),
+                    box_a.min().z().max(box_b.min().z()),
+                );
+                let max = Point3::new(
+                    box_a.max().x().min(box_b.max().x()),
+                    box_a.max().y().min(box_b.max().y()),
+                    box_a.max().z().min(box_b.max().z()),
+                );
+                // Boîtes disjointes : le résultat est vide, une boîte minuscule suffit
+                Some(Aabb::new(min, Point3::new(max.x().max(min.x()), max.y().max(min.y()), max.z().max(min.z()))))
+            }
+            CsgOp::Difference => Some(box_a),
+        };
+        Some(Csg { op, a, b, bbox })
+    }
+
+    // Bords du résultat le long du rayon, triés, avec des normales tournées vers l'extérieur du résultat
+    fn boundaries(&self, r: &Ray) -> Vec<HitRecord> {
+        // (t, vient de A, entrée, bord)
+        let mut events: Vec<(f64, bool, bool, HitRecord)> = Vec::new();
+        for (from_a, intervals) in [(true, self.a.intervals(r)), (false, self.b.intervals(r))] {
+            for interval in intervals {
+                events.push((interval.enter.t, from_a, true, interval.enter));
+                events.push((interval.exit.t, from_a, false, interval.exit));
+            }
+        }
+        events.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+        let (mut in_a, mut in_b) = (false, false);
+        let mut inside = false;
+        let mut out = Vec::new();
+        for (_, from_a, entering, mut rec) in events {
+            if from_a {
+                in_a = entering;
+            } else {
+                in_b = entering;
+            }
+            let now = self.op.inside(in_a, in_b);
+            if now == inside {
+                continue;
+            }
+            inside = now;
+            // Dans A moins B, la surface de B borde le résultat à l'envers
+            let mut outward = rec.outward_normal();
+            if !from_a && self.op == CsgOp::Difference {
+                outward = -outward;
+            }
+            rec.set_face_normal(r, outward);
+            out.push(rec);
+        }
+        out
+    }
+}
+
+impl Hittable for Csg {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        match self.boundaries(r).into_iter().find(|b| b.t > t_min && b.t < t_max) {
+            Some(boundary) => {
+                *rec = boundary;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    // Les bords alternent entrée / sortie : on les regroupe par deux
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        let mut boundaries = self.boundaries(r).into_iter();
+        let mut intervals = Vec::new();
+        while let (Some(enter), Some(exit)) = (boundaries.next(), boundaries.next()) {
+            intervals.push(Interval { enter, exit });
+        }
+        intervals
+    }
+}
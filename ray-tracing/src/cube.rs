@@ -1,78 +1,86 @@
-use std::rc::Rc;
+use std::sync::Arc;
+use crate::aabb::Aabb;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
-use crate::hittable::{HitRecord, Hittable};
+use crate::hittable::{self, HitRecord, Hittable, Interval};
 use crate::material::Material;
 
 pub struct Cube {
     min: Point3,  // Coin inférieur du cube (point minimum)
     max: Point3,  // Coin supérieur du cube (point maximum)
-    mat: Rc<dyn Material>,
+    mat: Arc<dyn Material>,
 }
 
 impl Cube {
-    pub fn new(min: Point3, max: Point3, mat: Rc<dyn Material>) -> Cube {
+    pub fn new(min: Point3, max: Point3, mat: Arc<dyn Material>) -> Cube {
         Cube { min, max, mat }
     }
 }
 impl Hittable for Cube {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
         // On vérifie les intersections sur chaque face du cube en utilisant la technique
-        // de "slab method" ou "AABB intersection" (Axis-Aligned Bounding Box)
-
-        // Vérifier l'intersection avec les limites sur l'axe X
-        let t_x_min = (self.min.x() - r.origin().x()) / r.direction().x();
-        let t_x_max = (self.max.x() - r.origin().x()) / r.direction().x();
+        // de "slab method" ou "AABB intersection" (Axis-Aligned Bounding Box) :
+        // sur chaque axe, le rayon est entre les deux plans du cube pendant [t0, t1]
+        let mut t_enter = f64::NEG_INFINITY;
+        let mut t_exit = f64::INFINITY;
+        let mut enter_axis = 0;
+        let mut exit_axis = 0;
+        for axis in 0..3 {
+            let t0 = (self.min[axis] - r.origin()[axis]) / r.direction()[axis];
+            let t1 = (self.max[axis] - r.origin()[axis]) / r.direction()[axis];
+            let (t0, t1) = if t0 > t1 { (t1, t0) } else { (t0, t1) };
+            if t0 > t_enter {
+                t_enter = t0;
+                enter_axis = axis;
+            }
+            if t1 < t_exit {
+                t_exit = t1;
+                exit_axis = axis;
+            }
+        }
 
-        let (t_x_min, t_x_max) = if t_x_min > t_x_max {
-            (t_x_max, t_x_min)
+        // Vérifier si les intersections sont valides : on garde l'entrée,
+        // ou la sortie si le rayon part de l'intérieur du cube (verre)
+        if t_enter >= t_exit {
+            return false;
+        }
+        let (t, axis, sign) = if t_enter > t_min && t_enter < t_max {
+            // On entre par la face opposée au sens de déplacement
+            (t_enter, enter_axis, -r.direction()[enter_axis].signum())
+        } else if t_exit > t_min && t_exit < t_max {
+            (t_exit, exit_axis, r.direction()[exit_axis].signum())
         } else {
-            (t_x_min, t_x_max)
+            return false;
         };
 
-        // Vérifier l'intersection avec les limites sur l'axe Y
-        let t_y_min = (self.min.y() - r.origin().y()) / r.direction().y();
-        let t_y_max = (self.max.y() - r.origin().y()) / r.direction().y();
+        rec.t = t;
+        rec.p = r.at(t);
+        let mut outward_normal = [0.0; 3];
+        outward_normal[axis] = sign;
+        rec.set_face_normal(r, Vec3::new(outward_normal[0], outward_normal[1], outward_normal[2]));
 
-        let (t_y_min, t_y_max) = if t_y_min > t_y_max {
-            (t_y_max, t_y_min)
-        } else {
-            (t_y_min, t_y_max)
+        // UV dans [0, 1] sur chaque face, à partir des deux autres axes
+        let (a, b) = match axis {
+            0 => (2, 1),
+            1 => (0, 2),
+            _ => (0, 1),
         };
+        rec.u = (rec.p[a] - self.min[a]) / (self.max[a] - self.min[a]);
+        rec.v = (rec.p[b] - self.min[b]) / (self.max[b] - self.min[b]);
+        rec.mat = Some(self.mat.clone());
+        true
+    }
 
-        // Vérifier l'intersection avec les limites sur l'axe Z
-        let t_z_min = (self.min.z() - r.origin().z()) / r.direction().z();
-        let t_z_max = (self.max.z() - r.origin().z()) / r.direction().z();
-
-        let (t_z_min, t_z_max) = if t_z_min > t_z_max {
-            (t_z_max, t_z_min)
-        } else {
-            (t_z_min, t_z_max)
-        };
-
-        // Trouver les intervalles d'intersection
-        let t_enter = t_x_min.max(t_y_min).max(t_z_min);
-        let t_exit = t_x_max.min(t_y_max).min(t_z_max);
-
-        // Vérifier si les intersections sont valides
-        if t_enter < t_exit && t_exit > t_min && t_enter < t_max {
-            rec.t = t_enter;
-            rec.p = r.at(rec.t);
-
-            // Calculer la normale à la face touchée
-            if t_enter == t_x_min || t_enter == t_x_max {
-                rec.set_face_normal(r, Vec3::new(1.0, 0.0, 0.0));
-            } else if t_enter == t_y_min || t_enter == t_y_max {
-                rec.set_face_normal(r, Vec3::new(0.0, 1.0, 0.0));
-            } else {
-                rec.set_face_normal(r, Vec3::new(0.0, 0.0, 1.0));
-            }
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(self.min, self.max))
+    }
 
-            rec.mat = Some(self.mat.clone());
-            return true;
-        }
+    fn is_solid(&self) -> bool {
+        true
+    }
 
-        false
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        hittable::convex_interval(self, r)
     }
 }
 
@@ -1,105 +1,192 @@
-use std::rc::Rc;
+use std::sync::Arc;
 use crate::vec3::{Point3, Vec3};
+use crate::aabb::Aabb;
+use crate::common::PI;
 use crate::ray::Ray;
 use crate::material::Material;
-use crate::hittable::{HitRecord, Hittable};
+use crate::hittable::{self, HitRecord, Hittable, Interval};
 
+/*
+Cylindre ou tronc de cône entre deux points, dans n'importe quelle orientation.
+Le rayon varie linéairement de radius0 (en p0) à radius1 (en p1) : égaux, c'est un cylindre.
+Les deux disques des extrémités sont optionnels (tube ouvert).
+*/
 pub struct Cylinder {
-    base: Point3,         // Base center of the cylinder
-    height: f64,          // Height of the cylinder
-    radius: f64,          // Radius of the cylinder
-    mat: Rc<dyn Material>, // Material of the cylinder
+    base: Point3,         // Centre du disque de départ (p0)
+    axis: Vec3,           // Axe unitaire, de p0 vers p1
+    height: f64,          // Distance entre p0 et p1
+    radius0: f64,         // Rayon en p0
+    radius1: f64,         // Rayon en p1
+    capped: bool,         // Disques fermant les extrémités
+    frame: (Vec3, Vec3),  // Deux directions perpendiculaires à l'axe, pour les coordonnées (u, v)
+    mat: Arc<dyn Material>, // Material of the cylinder
 }
 
 impl Cylinder {
-    pub fn new(base: Point3, height: f64, radius: f64, mat: Rc<dyn Material>) -> Cylinder {
+    // Cylindre vertical posé sur `base`, comme à l'origine
+    pub fn new(base: Point3, height: f64, radius: f64, mat: Arc<dyn Material>) -> Cylinder {
+        Cylinder::cone(base, radius, base + Vec3::new(0.0, height, 0.0), radius, mat)
+    }
+
+    pub fn between(p0: Point3, p1: Point3, radius: f64, mat: Arc<dyn Material>) -> Cylinder {
+        Cylinder::cone(p0, radius, p1, radius, mat)
+    }
+
+    // Tronc de cône : rayon radius0 en p0, radius1 en p1 (0 pour une pointe)
+    pub fn cone(p0: Point3, radius0: f64, p1: Point3, radius1: f64, mat: Arc<dyn Material>) -> Cylinder {
+        let height = (p1 - p0).vec_length();
+        let axis = (p1 - p0) / height;
+        // X et Z pour un axe vertical : l'habillage des cylindres existants ne change pas
+        let side = if axis.x().abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+        let e1 = Vec3::unit_vector(side - Vec3::dot(side, axis) * axis);
+        let e2 = Vec3::cross(e1, axis);
         Cylinder {
-            base,
+            base: p0,
+            axis,
             height,
-            radius,
+            radius0,
+            radius1,
+            capped: true,
+            frame: (e1, e2),
             mat,
         }
     }
-}
 
-impl Hittable for Cylinder {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        let mut hit_anything = false;
-        let mut closest_t = t_max;
+    // Sans disques aux extrémités : un tuyau, un abat-jour
+    pub fn open(mut self) -> Cylinder {
+        self.capped = false;
+        self
+    }
 
-        // Check for intersection with the cylindrical surface
+    // Variation du rayon par unité de hauteur
+    fn slope(&self) -> f64 {
+        (self.radius1 - self.radius0) / self.height
+    }
+
+    // Les disques reçoivent la projection du carré qui les entoure, ramenée à [0, 1]
+    fn cap_uv(&self, offset: Vec3, radius: f64) -> (f64, f64) {
+        (
+            Vec3::dot(offset, self.frame.0) / (2.0 * radius) + 0.5,
+            Vec3::dot(offset, self.frame.1) / (2.0 * radius) + 0.5,
+        )
+    }
+
+    // Paroi latérale : |q⊥|² = r(y)², avec q = p - base, y = q·axe et r(y) = radius0 + pente * y
+    fn hit_side(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let k = self.slope();
         let oc = r.origin() - self.base;
         let dir = r.direction();
+        let (oy, dy) = (Vec3::dot(oc, self.axis), Vec3::dot(dir, self.axis));
+        let r_origin = self.radius0 + k * oy;
 
-        let a = dir.x() * dir.x() + dir.z() * dir.z();
-        let half_b = oc.x() * dir.x() + oc.z() * dir.z();
-        let c = oc.x() * oc.x() + oc.z() * oc.z() - self.radius * self.radius;
+        let a = dir.length_squared() - dy * dy - k * k * dy * dy;
+        let half_b = Vec3::dot(oc, dir) - oy * dy - k * dy * r_origin;
+        let c = oc.length_squared() - oy * oy - r_origin * r_origin;
 
-        let discriminant = half_b * half_b - a * c;
-        if discriminant >= 0.0 {
-            let sqrt_d = discriminant.sqrt();
-
-            // Check the smaller root
-            let mut root = (-half_b - sqrt_d) / a;
-            let y_hit = oc.y() + root * dir.y();
-            if root > t_min && root < closest_t && y_hit >= 0.0 && y_hit <= self.height {
-                closest_t = root;
-                hit_anything = true;
-
-                rec.t = root;
-                rec.p = r.at(rec.t);
-                let outward_normal = Vec3::new(rec.p.x() - self.base.x(), 0.0, rec.p.z() - self.base.z()).normalize();
-                rec.set_face_normal(r, outward_normal);
-                rec.mat = Some(self.mat.clone());
+        let roots = if a.abs() < 1e-12 {
+            // Rayon parallèle à la génératrice du cône : une seule intersection
+            if half_b.abs() < 1e-12 {
+                return false;
+            }
+            [-c / (2.0 * half_b), f64::INFINITY]
+        } else {
+            let discriminant = half_b * half_b - a * c;
+            if discriminant < 0.0 {
+                return false;
             }
+            let sqrt_d = discriminant.sqrt();
+            let (t0, t1) = ((-half_b - sqrt_d) / a, (-half_b + sqrt_d) / a);
+            [t0.min(t1), t0.max(t1)]
+        };
 
-            // Check the larger root
-            root = (-half_b + sqrt_d) / a;
-            let y_hit = oc.y() + root * dir.y();
-            if root > t_min && root < closest_t && y_hit >= 0.0 && y_hit <= self.height {
-                closest_t = root;
-                hit_anything = true;
-
-                rec.t = root;
-                rec.p = r.at(rec.t);
-                let outward_normal = Vec3::new(rec.p.x() - self.base.x(), 0.0, rec.p.z() - self.base.z()).normalize();
-                rec.set_face_normal(r, outward_normal);
-                rec.mat = Some(self.mat.clone());
+        for root in roots {
+            let y = oy + root * dy;
+            if root <= t_min || root >= t_max || y < 0.0 || y > self.height {
+                continue;
             }
+            rec.t = root;
+            rec.p = r.at(root);
+            // Gradient de la surface : composante radiale, inclinée par la pente du cône
+            let radial = rec.p - self.base - y * self.axis;
+            let outward_normal = Vec3::unit_vector(radial - (self.radius0 + k * y) * k * self.axis);
+            rec.set_face_normal(r, outward_normal);
+            // u : angle autour de l'axe, v : hauteur relative
+            let (x, z) = (Vec3::dot(radial, self.frame.0), Vec3::dot(radial, self.frame.1));
+            rec.u = (f64::atan2(z, x) + PI) / (2.0 * PI);
+            rec.v = y / self.height;
+            rec.mat = Some(self.mat.clone());
+            return true;
         }
+        false
+    }
 
-        // Check for intersection with the bottom cap
-        let t_cap_bottom = (self.base.y() - r.origin().y()) / r.direction().y();
-        if t_cap_bottom > t_min && t_cap_bottom < closest_t {
-            let p_cap = r.at(t_cap_bottom);
-            let d = (p_cap.x() - self.base.x()).powi(2) + (p_cap.z() - self.base.z()).powi(2);
-            if d <= self.radius * self.radius {
-                closest_t = t_cap_bottom;
-                hit_anything = true;
-
-                rec.t = t_cap_bottom;
-                rec.p = p_cap;
-                rec.set_face_normal(r, Vec3::new(0.0, -1.0, 0.0)); // Normal points downward
-                rec.mat = Some(self.mat.clone());
-            }
+    fn hit_cap(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, top: bool) -> bool {
+        let (center, radius, normal) = if top {
+            (self.base + self.height * self.axis, self.radius1, self.axis)
+        } else {
+            (self.base, self.radius0, -self.axis)
+        };
+        let denom = Vec3::dot(r.direction(), self.axis);
+        if radius <= 0.0 || denom.abs() < 1e-12 {
+            return false;
+        }
+        let t = Vec3::dot(center - r.origin(), self.axis) / denom;
+        if t <= t_min || t >= t_max {
+            return false;
+        }
+        let p = r.at(t);
+        if (p - center).length_squared() > radius * radius {
+            return false;
         }
+        rec.t = t;
+        rec.p = p;
+        rec.set_face_normal(r, normal);
+        (rec.u, rec.v) = self.cap_uv(p - center, radius);
+        rec.mat = Some(self.mat.clone());
+        true
+    }
+}
+
+impl Hittable for Cylinder {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let mut closest_t = t_max;
+        let mut hit_anything = false;
 
-        // Check for intersection with the top cap
-        let t_cap_top = (self.base.y() + self.height - r.origin().y()) / r.direction().y();
-        if t_cap_top > t_min && t_cap_top < closest_t {
-            let p_cap = r.at(t_cap_top);
-            let d = (p_cap.x() - self.base.x()).powi(2) + (p_cap.z() - self.base.z()).powi(2);
-            if d <= self.radius * self.radius {
-                closest_t = t_cap_top;
-                hit_anything = true;
-
-                rec.t = t_cap_top;
-                rec.p = p_cap;
-                rec.set_face_normal(r, Vec3::new(0.0, 1.0, 0.0)); // Normal points upward
-                rec.mat = Some(self.mat.clone());
+        if self.hit_side(r, t_min, closest_t, rec) {
+            closest_t = rec.t;
+            hit_anything = true;
+        }
+        if self.capped {
+            for top in [false, true] {
+                if self.hit_cap(r, t_min, closest_t, rec, top) {
+                    closest_t = rec.t;
+                    hit_anything = true;
+                }
             }
         }
-
         hit_anything
     }
+
+    // Union des boîtes des deux disques : un disque de normale n déborde de r * sqrt(1 - n_i²) sur l'axe i
+    fn bounding_box(&self) -> Option<Aabb> {
+        let disk = |center: Point3, radius: f64| {
+            let extent = Vec3::new(
+                radius * (1.0 - self.axis.x() * self.axis.x()).max(0.0).sqrt(),
+                radius * (1.0 - self.axis.y() * self.axis.y()).max(0.0).sqrt(),
+                radius * (1.0 - self.axis.z() * self.axis.z()).max(0.0).sqrt(),
+            );
+            Aabb::new(center - extent, center + extent)
+        };
+        let top = self.base + self.height * self.axis;
+        Some(Aabb::surrounding_box(disk(self.base, self.radius0), disk(top, self.radius1)).pad())
+    }
+
+    // Un tube ouvert n'enferme pas de volume
+    fn is_solid(&self) -> bool {
+        self.capped
+    }
+
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        hittable::convex_interval(self, r)
+    }
 }
@@ -0,0 +1,461 @@
+/*
+Compression zlib (RFC 1950 / RFC 1951) minimale, utilisée pour écrire les PNG.
+LZ77 avec table de hachage sur 3 octets, puis codage en un seul bloc "Huffman fixe" :
+moins efficace que zlib niveau 9, mais sans dépendance et largement suffisant pour des images.
+La décompression (lecture des textures PNG) accepte en revanche les trois types de blocs.
+*/
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: usize = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+// Ecriture bit à bit, poids faible en premier comme l'exige deflate
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u64,
+    n_bits: u32,
+}
+
+impl BitWriter {
+    fn new(out: Vec<u8>) -> BitWriter {
+        BitWriter { out, acc: 0, n_bits: 0 }
+    }
+
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.acc |= (value as u64) << self.n_bits;
+        self.n_bits += count;
+        while self.n_bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.n_bits -= 8;
+        }
+    }
+
+    // Les codes de Huffman s'écrivent poids fort en premier : on les retourne
+    fn write_code(&mut self, code: u32, len: u32) {
+        let reversed = code.reverse_bits() >> (32 - len);
+        self.write_bits(reversed, len);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.n_bits > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+fn write_literal(w: &mut BitWriter, symbol: u32) {
+    // Table de Huffman fixe (RFC 1951, 3.2.6)
+    match symbol {
+        0..=143 => w.write_code(0x30 + symbol, 8),
+        144..=255 => w.write_code(0x190 + symbol - 144, 9),
+        256..=279 => w.write_code(symbol - 256, 7),
+        _ => w.write_code(0xC0 + symbol - 280, 8),
+    }
+}
+
+fn write_match(w: &mut BitWriter, length: usize, distance: usize) {
+    let li = LENGTH_BASE.iter().rposition(|&b| b as usize <= length).unwrap();
+    write_literal(w, 257 + li as u32);
+    w.write_bits((length - LENGTH_BASE[li] as usize) as u32, LENGTH_EXTRA[li] as u32);
+
+    let di = DIST_BASE.iter().rposition(|&b| b as usize <= distance).unwrap();
+    w.write_code(di as u32, 5);
+    w.write_bits((distance - DIST_BASE[di] as usize) as u32, DIST_EXTRA[di] as u32);
+}
+
+fn hash(data: &[u8], i: usize) -> usize {
+    let v = (data[i] as u32) << 16 | (data[i + 1] as u32) << 8 | data[i + 2] as u32;
+    (v.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { 0xEDB8_8320 ^ (crc >> 1) } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+// Flux zlib complet : en-tête, données compressées, somme Adler-32
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new(vec![0x78, 0x01]);
+    // Un seul bloc final (BFINAL = 1) en Huffman fixe (BTYPE = 01)
+    w.write_bits(1, 1);
+    w.write_bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+    let mut i = 0;
+    while i < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(data, i);
+            let mut candidate = head[h];
+            let mut chain = 0;
+            let max_len = MAX_MATCH.min(data.len() - i);
+            while candidate != usize::MAX && i - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = i - candidate;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW_SIZE];
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+                chain += 1;
+            }
+        }
+
+        let step = if best_len >= MIN_MATCH {
+            write_match(&mut w, best_len, best_dist);
+            best_len
+        } else {
+            write_literal(&mut w, data[i] as u32);
+            1
+        };
+        // On référence toutes les positions couvertes pour les prochaines recherches
+        for k in i..(i + step).min(data.len().saturating_sub(MIN_MATCH - 1)) {
+            let h = hash(data, k);
+            prev[k % WINDOW_SIZE] = head[h];
+            head[h] = k;
+        }
+        i += step;
+    }
+    write_literal(&mut w, 256); // fin de bloc
+
+    let mut out = w.finish();
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+// Lecture bit à bit, poids faible en premier
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u64,
+    n_bits: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.n_bits < count {
+            let byte = *self.data.get(self.pos).ok_or("unexpected end of compressed data")?;
+            self.acc |= (byte as u64) << self.n_bits;
+            self.pos += 1;
+            self.n_bits += 8;
+        }
+        let value = (self.acc & ((1u64 << count) - 1)) as u32;
+        self.acc >>= count;
+        self.n_bits -= count;
+        Ok(value)
+    }
+
+    // Les blocs non compressés commencent sur un octet entier
+    fn align(&mut self) {
+        self.acc = 0;
+        self.n_bits = 0;
+    }
+}
+
+// Code de Huffman canonique décrit par la longueur de chaque symbole
+struct Huffman {
+    counts: [u16; 16],   // nombre de codes de chaque longueur
+    symbols: Vec<u16>,   // symboles triés par longueur puis par valeur
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for len in 1..16 {
+            for (symbol, &l) in lengths.iter().enumerate() {
+                if l as usize == len {
+                    symbols.push(symbol as u16);
+                }
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    // Lit le code bit par bit en parcourant les longueurs croissantes (à la manière de puff.c)
+    fn decode(&self, r: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= r.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err("invalid Huffman code".to_string())
+    }
+}
+
+fn fixed_tables() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    for (symbol, len) in lengths.iter_mut().enumerate() {
+        *len = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+// Tables transmises dans le bloc, elles-mêmes codées par un premier code de Huffman
+fn dynamic_tables(r: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    const ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+    let n_lit = r.bits(5)? as usize + 257;
+    let n_dist = r.bits(5)? as usize + 1;
+    let n_code = r.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &i in &ORDER[..n_code] {
+        code_lengths[i] = r.bits(3)? as u8;
+    }
+    let code = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(n_lit + n_dist);
+    while lengths.len() < n_lit + n_dist {
+        let (value, repeat) = match code.decode(r)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or("repeat with no previous length")?;
+                (previous, 3 + r.bits(2)?)
+            }
+            17 => (0, 3 + r.bits(3)?),
+            _ => (0, 11 + r.bits(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() > n_lit + n_dist {
+        return Err("too many code lengths".to_string());
+    }
+    Ok((Huffman::new(&lengths[..n_lit]), Huffman::new(&lengths[n_lit..])))
+}
+
+fn inflate_block(r: &mut BitReader, out: &mut Vec<u8>, lit: &Huffman, dist: &Huffman) -> Result<(), String> {
+    loop {
+        let symbol = lit.decode(r)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let li = symbol - 257;
+                let length = LENGTH_BASE[li] as usize + r.bits(LENGTH_EXTRA[li] as u32)? as usize;
+                let di = dist.decode(r)? as usize;
+                if di >= 30 {
+                    return Err("invalid distance code".to_string());
+                }
+                let distance = DIST_BASE[di] as usize + r.bits(DIST_EXTRA[di] as u32)? as usize;
+                if distance > out.len() {
+                    return Err("distance too far back".to_string());
+                }
+                // Copie octet par octet : la source peut chevaucher ce qu'on écrit
+                let start = out.len() - distance;
+                for k in 0..length {
+                    out.push(out[start + k]);
+                }
+            }
+            _ => return Err("invalid literal/length code".to_string()),
+        }
+    }
+}
+
+// Décompresse un flux zlib complet et vérifie sa somme Adler-32
+pub fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 6 || data[0] & 0x0F != 8 || !u16::from_be_bytes([data[0], data[1]]).is_multiple_of(31) {
+        return Err("invalid zlib header".to_string());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("preset dictionaries are not supported".to_string());
+    }
+
+    let mut r = BitReader { data: &data[2..], pos: 0, acc: 0, n_bits: 0 };
+    let mut out = Vec::new();
+    loop {
+        let last = r.bits(1)? == 1;
+        match r.bits(2)? {
+            0 => {
+                r.align();
+                let header = r.data.get(r.pos..r.pos + 4).ok_or("unexpected end of compressed data")?;
+                let len = u16::from_le_bytes([header[0], header[1]]) as usize;
+                if len != !u16::from_le_bytes([header[2], header[3]]) as usize {
+                    return Err("corrupt stored block length".to_string());
+                }
+                let start = r.pos + 4;
+                let block = r.data.get(start..start + len).ok_or("unexpected end of compressed data")?;
+                out.extend_from_slice(block);
+                r.pos = start + len;
+            }
+            1 => {
+                let (lit, dist) = fixed_tables();
+                inflate_block(&mut r, &mut out, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(&mut r)?;
+                inflate_block(&mut r, &mut out, &lit, &dist)?;
+            }
+            _ => return Err("invalid block type".to_string()),
+        }
+        if last {
+            break;
+        }
+    }
+
+    // La somme suit le dernier bloc, alignée sur un octet
+    let end = r.pos;
+    let checksum = data[2..]
+        .get(end..end + 4)
+        .ok_or("missing Adler-32 checksum")?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&out) {
+        return Err("Adler-32 checksum mismatch".to_string());
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Octets pseudo-aléatoires (xorshift) : incompressibles
+    fn noise(len: usize) -> Vec<u8> {
+        let mut x = 0x2545_F491_4F6C_DD1Du64;
+        (0..len)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                (x >> 32) as u8
+            })
+            .collect()
+    }
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let compressed = zlib_compress(data);
+        assert_eq!(zlib_decompress(&compressed).unwrap(), data);
+        compressed
+    }
+
+    #[test]
+    fn checksums_match_reference_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        // Assez long pour que les sommes de l'Adler-32 soient réduites en cours de route
+        assert_eq!(adler32(&[0xFF; 100_000]), 0x149A_302C);
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        round_trip(&[]);
+    }
+
+    #[test]
+    fn round_trip_incompressible() {
+        let data = noise(70_000);
+        let compressed = round_trip(&data);
+        // Huffman fixe : un littéral d'octet haut coûte 9 bits, le surcoût reste borné
+        assert!(compressed.len() < data.len() * 9 / 8 + 16);
+    }
+
+    #[test]
+    fn round_trip_repetitive() {
+        let zeros = vec![0u8; 100_000];
+        assert!(round_trip(&zeros).len() < 1_000);
+
+        let text = b"abcabcabd".repeat(5_000);
+        assert!(round_trip(&text).len() < 1_000);
+
+        // Un motif répété à une distance proche de la fenêtre de 32 Ko
+        let block = noise(32_000);
+        let data = [block.clone(), block.clone(), block].concat();
+        assert!(round_trip(&data).len() < 40_000);
+    }
+
+    #[test]
+    fn decompresses_reference_zlib_streams() {
+        // Produits par zlib : un bloc Huffman dynamique, puis un bloc non compressé
+        let dynamic = "78dacd8dc10dc3300c0357e1004116e80af97600452150038a6cc84e816c5f3beda323e44b1e8f0b117266479168d80813a8ec0c99105cb36fa9a11e31f25a5e0c82ad3717fe665418a1893663b9a5eae963bd974ea8498acb5595cec780be37bf255d7307fe05588dc7fc01be7d67d7";
+        let mut expected = b"Le rayon part de la camera, rebondit sur la sphere et repart vers le ciel. ".repeat(3);
+        expected.extend_from_slice(b"Une lampe eclaire la scene; le rayon repart encore vers le ciel bleu.");
+        assert_eq!(zlib_decompress(&hex(dynamic)).unwrap(), expected);
+
+        let stored = "7801010c00f3ff73746f72656420626c6f636b1f8004bd";
+        assert_eq!(zlib_decompress(&hex(stored)).unwrap(), b"stored block");
+    }
+
+    #[test]
+    fn rejects_corrupt_streams() {
+        let mut compressed = zlib_compress(b"du texte, du texte, du texte");
+        let last = compressed.len() - 1;
+        compressed[last] ^= 1;
+        assert!(zlib_decompress(&compressed).is_err());
+        assert!(zlib_decompress(&[0x78, 0x01]).is_err());
+    }
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,223 @@
+use crate::color::Color;
+use crate::common::{self, RenderRng, PI};
+use crate::image::Picture;
+use crate::matrix::Mat4;
+use crate::vec3::Vec3;
+
+/*
+Carte d'environnement équirectangulaire (latitude / longitude), en radiance linéaire (.hdr, PFM).
+Le centre de l'image est vu dans la direction -Z, le haut de l'image correspond à +Y :
+la même convention que la caméra equirectangular, qui redonne donc l'image d'origine.
+
+Pour l'échantillonnage, chaque pixel reçoit un poids luminance x sin(latitude) (les pixels près
+des pôles couvrent un plus petit angle solide) ; une ligne est tirée selon la somme de ses poids,
+puis une colonne dans cette ligne. Un soleil de quelques pixels reçoit ainsi la plupart des tirages.
+*/
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+    intensity: f64,
+    to_map: Mat4,   // rotation inverse : du monde vers la carte
+    to_world: Mat4,
+    row_cdf: Vec<f64>,       // height + 1 valeurs croissantes, de 0 à 1
+    column_cdf: Vec<f64>,    // (width + 1) valeurs par ligne
+    total_weight: f64,
+}
+
+fn luminance(c: Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
+
+// Fonction de répartition normalisée, et la somme des poids
+fn cdf(weights: impl Iterator<Item = f64>) -> (Vec<f64>, f64) {
+    let mut out = vec![0.0];
+    let mut sum = 0.0;
+    for w in weights {
+        sum += w;
+        out.push(sum);
+    }
+    if sum > 0.0 {
+        for value in out.iter_mut() {
+            *value /= sum;
+        }
+    }
+    (out, sum)
+}
+
+// Indice de l'intervalle de la CDF contenant x, et la position relative de x dans cet intervalle
+fn find_interval(cdf: &[f64], x: f64) -> (usize, f64) {
+    let i = cdf.partition_point(|&c| c <= x).clamp(1, cdf.len() - 1) - 1;
+    let width = cdf[i + 1] - cdf[i];
+    let offset = if width > 0.0 { (x - cdf[i]) / width } else { 0.5 };
+    (i, offset.clamp(0.0, 1.0))
+}
+
+impl EnvironmentMap {
+    // rotation : degrés autour de l'axe vertical
+    pub fn new(picture: Picture, rotation: f64, intensity: f64) -> EnvironmentMap {
+        let (width, height) = (picture.width, picture.height);
+        let mut column_cdf = Vec::with_capacity((width + 1) * height);
+        let mut row_weights = Vec::with_capacity(height);
+        for j in 0..height {
+            let sin_theta = f64::sin(PI * (j as f64 + 0.5) / height as f64);
+            let row = &picture.pixels[j * width..(j + 1) * width];
+            let (row_cdf, sum) = cdf(row.iter().map(|&c| luminance(c).max(0.0) * sin_theta));
+            column_cdf.extend(row_cdf);
+            row_weights.push(sum);
+        }
+        let (row_cdf, total_weight) = cdf(row_weights.into_iter());
+
+        let to_world = Mat4::rotate(Vec3::new(0.0, 1.0, 0.0), rotation);
+        EnvironmentMap {
+            width,
+            height,
+            pixels: picture.pixels,
+            intensity,
+            to_map: to_world.transpose(),
+            to_world,
+            row_cdf,
+            column_cdf,
+            total_weight,
+        }
+    }
+
+    // Direction de la carte (non tournée) vers coordonnées (u, v) dans [0, 1], v = 0 en haut
+    fn direction_to_uv(d: Vec3) -> (f64, f64) {
+        let d = Vec3::unit_vector(d);
+        let u = 0.5 + f64::atan2(d.x(), -d.z()) / (2.0 * PI);
+        let v = f64::acos(d.y().clamp(-1.0, 1.0)) / PI;
+        (u, v)
+    }
+
+    fn pixel(&self, u: f64, v: f64) -> (usize, usize) {
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+        (i, j)
+    }
+
+    pub fn radiance(&self, direction: Vec3) -> Color {
+        let (u, v) = EnvironmentMap::direction_to_uv(self.to_map.transform_vector(direction));
+        let (i, j) = self.pixel(u, v);
+        self.intensity * self.pixels[j * self.width + i]
+    }
+
+    // Densité par angle solide : densité sur l'image, divisée par l'aire 2 PI² sin(theta) d'un élément de carte
+    fn pdf_uv(&self, u: f64, v: f64) -> f64 {
+        let (i, j) = self.pixel(u, v);
+        let sin_theta = f64::sin(PI * v);
+        if self.total_weight <= 0.0 || sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let row = &self.column_cdf[j * (self.width + 1)..(j + 1) * (self.width + 1)];
+        let row_probability = self.row_cdf[j + 1] - self.row_cdf[j];
+        let column_probability = row[i + 1] - row[i];
+        let image_pdf = row_probability * column_probability * (self.width * self.height) as f64;
+        image_pdf / (2.0 * PI * PI * sin_theta)
+    }
+
+    pub fn pdf_value(&self, direction: Vec3) -> f64 {
+        let (u, v) = EnvironmentMap::direction_to_uv(self.to_map.transform_vector(direction));
+        self.pdf_uv(u, v)
+    }
+
+    // Direction tirée selon la luminance, et sa densité ; None pour une carte entièrement noire
+    pub fn sample(&self, rng: &mut RenderRng) -> Option<(Vec3, f64)> {
+        if self.total_weight <= 0.0 {
+            return None;
+        }
+        let (j, dv) = find_interval(&self.row_cdf, common::random_double(rng));
+        let row = &self.column_cdf[j * (self.width + 1)..(j + 1) * (self.width + 1)];
+        let (i, du) = find_interval(row, common::random_double(rng));
+        let u = (i as f64 + du) / self.width as f64;
+        let v = (j as f64 + dv) / self.height as f64;
+
+        let pdf = self.pdf_uv(u, v);
+        if pdf <= 0.0 {
+            return None;
+        }
+        // Inverse de direction_to_uv
+        let (phi, theta) = ((u - 0.5) * 2.0 * PI, v * PI);
+        let local = Vec3::new(theta.sin() * phi.sin(), theta.cos(), -theta.sin() * phi.cos());
+        Some((self.to_world.transform_vector(local), pdf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Carte 16x8 sombre, avec un soleil d'un pixel et une ligne entièrement noire
+    fn sunny_map() -> EnvironmentMap {
+        let (width, height) = (16, 8);
+        let mut pixels = vec![Color::new(0.1, 0.2, 0.3); width * height];
+        pixels[2 * width + 5] = Color::new(500.0, 500.0, 400.0);
+        for pixel in &mut pixels[6 * width..7 * width] {
+            *pixel = Color::new(0.0, 0.0, 0.0);
+        }
+        EnvironmentMap::new(Picture { width, height, pixels }, 30.0, 1.0)
+    }
+
+    fn assert_normalised(cdf: &[f64]) {
+        assert_eq!(cdf[0], 0.0);
+        assert!((cdf[cdf.len() - 1] - 1.0).abs() < 1e-12);
+        assert!(cdf.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn cdfs_are_monotonic_and_normalised() {
+        let map = sunny_map();
+        assert_eq!(map.row_cdf.len(), map.height + 1);
+        assert_normalised(&map.row_cdf);
+        for (j, row) in map.column_cdf.chunks(map.width + 1).enumerate() {
+            if j == 6 {
+                // Ligne noire : jamais tirée, et sa fonction de répartition reste nulle
+                assert_eq!(map.row_cdf[7], map.row_cdf[6]);
+                assert!(row.iter().all(|&c| c == 0.0));
+            } else {
+                assert_normalised(row);
+            }
+        }
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        // Somme de la densité sur une grille fine de la sphère, pondérée par l'angle solide
+        let map = sunny_map();
+        let (nu, nv) = (256, 128);
+        let mut total = 0.0;
+        for j in 0..nv {
+            let v = (j as f64 + 0.5) / nv as f64;
+            let solid_angle = 2.0 * PI * PI * f64::sin(PI * v) / (nu * nv) as f64;
+            for i in 0..nu {
+                total += map.pdf_uv((i as f64 + 0.5) / nu as f64, v) * solid_angle;
+            }
+        }
+        assert!((total - 1.0).abs() < 1e-3, "integral {}", total);
+    }
+
+    #[test]
+    fn samples_follow_the_luminance() {
+        let map = sunny_map();
+        let mut rng = common::pixel_rng(7, 0, 0);
+        let mut sun = 0;
+        for _ in 0..2_000 {
+            let (direction, pdf) = map.sample(&mut rng).unwrap();
+            // La densité renvoyée est celle de la direction, calculée dans l'autre sens
+            assert!((map.pdf_value(direction) - pdf).abs() <= 1e-6 * pdf);
+            let (u, v) = EnvironmentMap::direction_to_uv(map.to_map.transform_vector(direction));
+            if map.pixel(u, v) == (5, 2) {
+                sun += 1;
+            }
+        }
+        assert!(sun > 1_800, "{} samples on the sun", sun);
+    }
+
+    #[test]
+    fn black_map_is_never_sampled() {
+        let picture = Picture { width: 4, height: 2, pixels: vec![Color::new(0.0, 0.0, 0.0); 8] };
+        let map = EnvironmentMap::new(picture, 0.0, 1.0);
+        assert!(map.sample(&mut common::pixel_rng(1, 0, 0)).is_none());
+        assert_eq!(map.pdf_value(Vec3::new(0.0, 0.0, -1.0)), 0.0);
+    }
+}
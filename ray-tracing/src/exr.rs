@@ -0,0 +1,353 @@
+use std::io::{self, Write};
+
+use crate::deflate;
+use crate::render::Framebuffer;
+
+/*
+Ecriture OpenEXR minimale : une seule partie, lignes de balayage (scanline), canaux R, G, B.
+Les valeurs sont la radiance linéaire du framebuffer, sans exposition ni tone mapping,
+en demi-flottants (16 bits, suffisant pour la compo) ou en flottants 32 bits.
+Compression : aucune, ou ZIP (zlib par blocs de 16 lignes, après un prédicteur sur les octets).
+*/
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ExrPixel {
+    Half,
+    Float,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ExrCompression {
+    None,
+    Zip,
+}
+
+impl ExrPixel {
+    pub fn from_name(name: &str) -> Option<ExrPixel> {
+        match name.to_ascii_lowercase().as_str() {
+            "half" => Some(ExrPixel::Half),
+            "float" => Some(ExrPixel::Float),
+            _ => None,
+        }
+    }
+
+    // Codes de la spécification : 1 = HALF, 2 = FLOAT
+    fn code(self) -> i32 {
+        match self {
+            ExrPixel::Half => 1,
+            ExrPixel::Float => 2,
+        }
+    }
+
+    fn write(self, out: &mut Vec<u8>, value: f64) {
+        match self {
+            ExrPixel::Half => out.extend_from_slice(&f32_to_half(value as f32).to_le_bytes()),
+            ExrPixel::Float => out.extend_from_slice(&(value as f32).to_le_bytes()),
+        }
+    }
+}
+
+impl ExrCompression {
+    pub fn from_name(name: &str) -> Option<ExrCompression> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Some(ExrCompression::None),
+            "zip" => Some(ExrCompression::Zip),
+            _ => None,
+        }
+    }
+
+    // Codes de la spécification : 0 = NO_COMPRESSION, 3 = ZIP_COMPRESSION (16 lignes par bloc)
+    fn code(self) -> u8 {
+        match self {
+            ExrCompression::None => 0,
+            ExrCompression::Zip => 3,
+        }
+    }
+
+    fn lines_per_block(self) -> usize {
+        match self {
+            ExrCompression::None => 1,
+            ExrCompression::Zip => 16,
+        }
+    }
+}
+
+// Conversion f32 -> f16 (IEEE 754 binary16), arrondi au plus proche pair.
+// Au-delà de 65504 la valeur devient infinie, en dessous de 2^-24 elle s'annule
+fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        // Infini ou NaN (on garde un bit de mantisse pour rester NaN)
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exponent <= 0 {
+        // Nombre dénormalisé en f16 : on décale la mantisse avec son 1 implicite
+        if half_exponent < -10 {
+            return sign;
+        }
+        let full = mantissa | 0x80_0000;
+        let shift = (14 - half_exponent) as u32;
+        let mut half = full >> shift;
+        let rest = full & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        if rest > halfway || (rest == halfway && half & 1 == 1) {
+            half += 1;
+        }
+        return sign | half as u16;
+    }
+
+    let mut half = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    if rest > 0x1000 || (rest == 0x1000 && half & 1 == 1) {
+        // Une retenue peut passer dans l'exposant, jusqu'à l'infini : c'est le bon résultat
+        half += 1;
+    }
+    sign | half as u16
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+// Prédicteur ZIP d'OpenEXR : octets pairs puis impairs, puis différences successives (+128)
+fn zip_block(raw: &[u8]) -> Vec<u8> {
+    let mut shuffled = Vec::with_capacity(raw.len());
+    shuffled.extend(raw.iter().step_by(2));
+    shuffled.extend(raw.iter().skip(1).step_by(2));
+
+    let mut previous = shuffled.first().copied().unwrap_or(0);
+    for byte in shuffled.iter_mut().skip(1) {
+        let current = *byte;
+        *byte = current.wrapping_sub(previous).wrapping_add(128);
+        previous = current;
+    }
+    deflate::zlib_compress(&shuffled)
+}
+
+pub fn write_exr(
+    out: &mut impl Write,
+    fb: &Framebuffer,
+    pixel: ExrPixel,
+    compression: ExrCompression,
+) -> io::Result<()> {
+    let (width, height) = (fb.width(), fb.height());
+
+    // Nombre magique et version 2, scanline simple
+    let mut file = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+
+    // Les canaux sont rangés par ordre alphabétique : B, G, R
+    let mut channels = Vec::new();
+    for name in ["B", "G", "R"] {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&pixel.code().to_le_bytes());
+        channels.extend_from_slice(&[0, 0, 0, 0]); // pLinear et 3 octets réservés
+        channels.extend_from_slice(&1i32.to_le_bytes()); // pas de sous-échantillonnage
+        channels.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+
+    let mut window = Vec::with_capacity(16);
+    for v in [0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend_from_slice(&v.to_le_bytes());
+    }
+
+    attribute(&mut file, "channels", "chlist", &channels);
+    attribute(&mut file, "compression", "compression", &[compression.code()]);
+    attribute(&mut file, "dataWindow", "box2i", &window);
+    attribute(&mut file, "displayWindow", "box2i", &window);
+    attribute(&mut file, "lineOrder", "lineOrder", &[0]); // de haut en bas
+    attribute(&mut file, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    attribute(&mut file, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(&mut file, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    file.push(0);
+
+    // Blocs de lignes : pour chaque ligne, tous les B, puis tous les G, puis tous les R
+    let lines = compression.lines_per_block();
+    let mut blocks = Vec::new();
+    for first in (0..height).step_by(lines) {
+        let mut raw = Vec::new();
+        for y in first..(first + lines).min(height) {
+            for channel in [2, 1, 0] {
+                for x in 0..width {
+                    pixel.write(&mut raw, fb.get(x, y)[channel]);
+                }
+            }
+        }
+        let data = match compression {
+            ExrCompression::None => raw,
+            // Un bloc que la compression agrandit est stocké tel quel : le lecteur le reconnaît à sa taille
+            ExrCompression::Zip => {
+                let packed = zip_block(&raw);
+                if packed.len() < raw.len() { packed } else { raw }
+            }
+        };
+        blocks.push((first, data));
+    }
+
+    // Table des positions de chaque bloc dans le fichier, puis les blocs
+    let mut offset = file.len() + 8 * blocks.len();
+    for (_, data) in &blocks {
+        file.extend_from_slice(&(offset as u64).to_le_bytes());
+        offset += 8 + data.len();
+    }
+    for (first, data) in &blocks {
+        file.extend_from_slice(&(*first as i32).to_le_bytes());
+        file.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        file.extend_from_slice(data);
+    }
+    out.write_all(&file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    // f16 -> f32 exact, pour vérifier la conversion dans l'autre sens
+    fn half_to_f32(half: u16) -> f32 {
+        let sign = if half & 0x8000 != 0 { -1.0 } else { 1.0 };
+        let exponent = ((half >> 10) & 0x1f) as i32;
+        let mantissa = (half & 0x3ff) as f32;
+        match exponent {
+            0 => sign * mantissa * 2f32.powi(-24),
+            0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+            0x1f => f32::NAN,
+            _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+        }
+    }
+
+    #[test]
+    fn half_conversion_is_exact_on_every_half() {
+        for half in 0..=u16::MAX {
+            if half & 0x7c00 == 0x7c00 && half & 0x3ff != 0 {
+                continue; // NaN
+            }
+            assert_eq!(f32_to_half(half_to_f32(half)), half, "half {:#06x}", half);
+        }
+    }
+
+    #[test]
+    fn half_conversion_rounds_to_nearest_even() {
+        let ulp = 2f32.powi(-10); // écart entre deux demi-flottants dans [1, 2[
+        assert_eq!(f32_to_half(1.0 + ulp / 2.0), 0x3c00); // à mi-chemin : vers la mantisse paire
+        assert_eq!(f32_to_half(1.0 + 3.0 * ulp / 2.0), 0x3c02);
+        assert_eq!(f32_to_half(1.0 + ulp / 2.0 + 2f32.powi(-20)), 0x3c01);
+        assert_eq!(f32_to_half(1.0 + ulp / 2.0 - 2f32.powi(-20)), 0x3c00);
+        // La retenue d'arrondi passe dans l'exposant : 2 - ulp/4 donne 2
+        assert_eq!(f32_to_half(2.0 - ulp / 4.0), 0x4000);
+    }
+
+    #[test]
+    fn half_conversion_of_denormals() {
+        let tiny = 2f32.powi(-24); // plus petit dénormalisé
+        assert_eq!(f32_to_half(tiny), 0x0001);
+        assert_eq!(f32_to_half(1023.0 * tiny), 0x03ff);
+        assert_eq!(f32_to_half(2f32.powi(-14)), 0x0400); // plus petit normalisé
+        assert_eq!(f32_to_half(tiny / 2.0), 0x0000); // à mi-chemin de 0 : pair
+        assert_eq!(f32_to_half(tiny * 0.75), 0x0001);
+        assert_eq!(f32_to_half(tiny * 1.5), 0x0002);
+        assert_eq!(f32_to_half(tiny * 2.5), 0x0002);
+        assert_eq!(f32_to_half(-tiny), 0x8001);
+        assert_eq!(f32_to_half(1e-10), 0x0000);
+        // La retenue du plus grand dénormalisé donne le plus petit normalisé
+        assert_eq!(f32_to_half(1023.75 * tiny), 0x0400);
+    }
+
+    #[test]
+    fn half_conversion_of_special_values() {
+        assert_eq!(f32_to_half(0.0), 0x0000);
+        assert_eq!(f32_to_half(-0.0), 0x8000);
+        assert_eq!(f32_to_half(65504.0), 0x7bff); // plus grand demi-flottant fini
+        assert_eq!(f32_to_half(65519.0), 0x7bff);
+        assert_eq!(f32_to_half(65520.0), 0x7c00); // à mi-chemin de 65536 : infini
+        assert_eq!(f32_to_half(1e6), 0x7c00);
+        assert_eq!(f32_to_half(-1e6), 0xfc00);
+        assert_eq!(f32_to_half(f32::INFINITY), 0x7c00);
+        assert_eq!(f32_to_half(f32::NEG_INFINITY), 0xfc00);
+        let nan = f32_to_half(f32::NAN);
+        assert!(nan & 0x7c00 == 0x7c00 && nan & 0x3ff != 0);
+    }
+
+    // En-tête attendu pour une image 3x20, écrit octet par octet d'après la spécification
+    fn expected_header(pixel: u8, compression: u8) -> Vec<u8> {
+        let hex = format!(
+            "762f310102000000\
+             6368616e6e656c730063686c6973740037000000\
+             4200{p:02x}000000000000000100000001000000\
+             4700{p:02x}000000000000000100000001000000\
+             5200{p:02x}00000000000000010000000100000000\
+             636f6d7072657373696f6e00636f6d7072657373696f6e0001000000{c:02x}\
+             6461746157696e646f7700626f783269001000000000000000000000000200000013000000\
+             646973706c617957696e646f7700626f783269001000000000000000000000000200000013000000\
+             6c696e654f72646572006c696e654f72646572000100000000\
+             706978656c417370656374526174696f00666c6f617400040000000000803f\
+             73637265656e57696e646f7743656e7465720076326600080000000000000000000000\
+             73637265656e57696e646f77576964746800666c6f617400040000000000803f\
+             00",
+            p = pixel,
+            c = compression
+        );
+        (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect()
+    }
+
+    fn read_u64(bytes: &[u8], at: usize) -> usize {
+        u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap()) as usize
+    }
+
+    fn read_i32(bytes: &[u8], at: usize) -> usize {
+        i32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn header_and_offset_table() {
+        let (width, height) = (3, 20);
+        let mut fb = Framebuffer::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                fb.set(x, y, Color::new(x as f64, y as f64 / 4.0, 0.5));
+            }
+        }
+
+        for (pixel, size) in [(ExrPixel::Half, 2), (ExrPixel::Float, 4)] {
+            for compression in [ExrCompression::None, ExrCompression::Zip] {
+                let mut file = Vec::new();
+                write_exr(&mut file, &fb, pixel, compression).unwrap();
+                let header = expected_header(pixel.code() as u8, compression.code());
+                assert_eq!(&file[..header.len()], &header[..]);
+
+                // Une entrée de table par bloc, chaque entrée pointe sur (première ligne, taille, données)
+                let lines = compression.lines_per_block();
+                let blocks = height.div_ceil(lines);
+                let mut expected = header.len() + 8 * blocks;
+                for block in 0..blocks {
+                    let offset = read_u64(&file, header.len() + 8 * block);
+                    assert_eq!(offset, expected);
+                    assert_eq!(read_i32(&file, offset), block * lines);
+                    let data_size = read_i32(&file, offset + 4);
+                    let raw_size = lines.min(height - block * lines) * width * 3 * size;
+                    match compression {
+                        ExrCompression::None => assert_eq!(data_size, raw_size),
+                        ExrCompression::Zip => assert!(data_size < raw_size),
+                    }
+                    expected = offset + 8 + data_size;
+                }
+                assert_eq!(file.len(), expected);
+            }
+        }
+    }
+}
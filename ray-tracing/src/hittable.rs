@@ -1,5 +1,7 @@
-use std::rc::Rc;
+use std::sync::Arc;
  
+use crate::aabb::Aabb;
+use crate::common::RenderRng;
 use crate::material::Material;
 use crate::ray::Ray;
 use crate::vec3::{Point3, Vec3};
@@ -8,8 +10,10 @@ use crate::vec3::{Point3, Vec3};
 pub struct HitRecord {
     pub p: Point3, // les point d'untersections
     pub normal: Vec3, // vecteur pointant vers l'extérieur de la surface qu'elle rencontre (direction)
-    pub mat: Option<Rc<dyn Material>>, 
+    pub mat: Option<Arc<dyn Material>>, 
     pub t: f64, // distance entre intersection et la cam
+    pub u: f64, // coordonnées de surface (texture) du point touché
+    pub v: f64,
     pub front_face: bool,
 }
 //Cette structure ne stocke que l'intersection la plus proche (et donc la plus pertinente pour l'affichage)
@@ -29,10 +33,108 @@ impl HitRecord {
             -outward_normal
         };
     }
+
+    // Normale sortante de la surface, quel que soit le côté d'où vient le rayon
+    pub fn outward_normal(&self) -> Vec3 {
+        if self.front_face { self.normal } else { -self.normal }
+    }
+}
+
+// Portion du rayon à l'intérieur d'un solide : points d'entrée et de sortie
+#[derive(Clone)]
+pub struct Interval {
+    pub enter: HitRecord,
+    pub exit: HitRecord,
 }
  
-pub trait Hittable {
+pub trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
     // rayon , 0.0 (camera), INFINI, le HitRecord créé par défault
 
+    // Intersection pour le rendu : un milieu participant y tire sa distance de collision avec `rng`.
+    // Les surfaces n'ont rien à tirer ; les conteneurs (listes, BVH, transformations) transmettent `rng`
+    fn hit_sampled(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, _rng: &mut RenderRng) -> bool {
+        self.hit(ray, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb>;
+    // None pour les objets infinis (Plane) : ils ne peuvent pas être rangés dans une BVH
+
+    // Échantillonnage des lampes surfaciques : densité (par angle solide) de viser l'objet
+    // depuis `origin` dans la direction `direction`, 0 si l'objet ne sait pas être visé
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    // Direction (non normalisée) depuis `origin` vers un point tiré sur l'objet
+    fn random(&self, _origin: Point3, _rng: &mut RenderRng) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // Solide fermé (sphère, cube, cylindre avec ses disques...) : utilisable dans une CSG
+    fn is_solid(&self) -> bool {
+        false
+    }
+
+    // CSG : toutes les portions du rayon à l'intérieur du solide, triées, sur toute la droite
+    // (t négatifs compris). Vide pour les objets qui n'enferment pas de volume
+    fn intervals(&self, _ray: &Ray) -> Vec<Interval> {
+        Vec::new()
+    }
+
+    // Rayons d'ombre : part de la lumière qui traverse l'objet entre t_min et t_max.
+    // Une surface arrête tout ; un milieu participant en laisse passer une partie (estimée avec `rng`)
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64, _rng: &mut RenderRng) -> f64 {
+        let mut rec = HitRecord::new();
+        if self.hit(ray, t_min, t_max, &mut rec) { 0.0 } else { 1.0 }
+    }
+}
+
+// Solide convexe : le rayon y entre au premier point touché et en ressort au suivant
+pub fn convex_interval(object: &dyn Hittable, r: &Ray) -> Vec<Interval> {
+    let mut enter = HitRecord::new();
+    if !object.hit(r, f64::NEG_INFINITY, f64::INFINITY, &mut enter) {
+        return Vec::new();
+    }
+    let mut exit = HitRecord::new();
+    if !object.hit(r, enter.t, f64::INFINITY, &mut exit) {
+        // Rayon tangent : il effleure le solide sans le traverser
+        return Vec::new();
+    }
+    vec![Interval { enter, exit }]
+}
+
+// Un objet partagé (lampe présente à la fois dans le monde et dans la liste des lampes)
+impl<T: Hittable + ?Sized> Hittable for Arc<T> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        (**self).hit(ray, t_min, t_max, rec)
+    }
+
+    fn hit_sampled(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, rng: &mut RenderRng) -> bool {
+        (**self).hit_sampled(ray, t_min, t_max, rec, rng)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut RenderRng) -> Vec3 {
+        (**self).random(origin, rng)
+    }
+
+    fn is_solid(&self) -> bool {
+        (**self).is_solid()
+    }
+
+    fn intervals(&self, ray: &Ray) -> Vec<Interval> {
+        (**self).intervals(ray)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut RenderRng) -> f64 {
+        (**self).transmittance(ray, t_min, t_max, rng)
+    }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
+use crate::aabb::Aabb;
+use crate::common::{Rng, RenderRng};
 use crate::hittable::{HitRecord, Hittable};
 use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
  
 #[derive(Default)]
 pub struct HittableList {
@@ -23,23 +26,79 @@ impl HittableList {
     pub fn add(&mut self, object: Box<dyn Hittable>) {
         self.objects.push(object);
     }
-}
- 
-impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn into_objects(self) -> Vec<Box<dyn Hittable>> {
+        self.objects
+    }
+
+    // Intersection la plus proche, `hit_object` testant un objet jusqu'à une distance donnée
+    fn closest<F>(&self, t_max: f64, rec: &mut HitRecord, mut hit_object: F) -> bool
+    where
+        F: FnMut(&dyn Hittable, f64, &mut HitRecord) -> bool,
+    {
         let mut temp_rec = HitRecord::new();
         let mut hit_anything = false;
         let mut closest_so_far = t_max;
- 
+
         for object in &self.objects {
-            if object.hit(ray, t_min, closest_so_far, &mut temp_rec) {
+            if hit_object(object.as_ref(), closest_so_far, &mut temp_rec) {
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
                 *rec = temp_rec.clone();
             }
             // permet de d'enregistrer dans HitRecord, le point de contact le plus proche de la cam
         }
- 
+
         hit_anything
     }
+}
+ 
+impl Hittable for HittableList {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.closest(t_max, rec, |object, t_max, rec| object.hit(ray, t_min, t_max, rec))
+    }
+
+    fn hit_sampled(&self, ray: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, rng: &mut RenderRng) -> bool {
+        self.closest(t_max, rec, |object, t_max, rec| object.hit_sampled(ray, t_min, t_max, rec, rng))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // La boîte de la liste englobe celles de tous ses objets
+        let mut output: Option<Aabb> = None;
+        for object in &self.objects {
+            let bbox = object.bounding_box()?;
+            output = Some(match output {
+                Some(b) => Aabb::surrounding_box(b, bbox),
+                None => bbox,
+            });
+        }
+        output
+    }
+
+    // Vue comme une lampe : on vise un objet au hasard, la densité est donc la moyenne des densités
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let sum: f64 = self.objects.iter().map(|o| o.pdf_value(origin, direction)).sum();
+        sum / self.objects.len().max(1) as f64
+    }
+
+    fn random(&self, origin: Point3, rng: &mut RenderRng) -> Vec3 {
+        let index = rng.gen_range(0..self.objects.len());
+        self.objects[index].random(origin, rng)
+    }
+
+    // Les objets traversés atténuent la lumière l'un après l'autre
+    fn transmittance(&self, ray: &Ray, t_min: f64, t_max: f64, rng: &mut RenderRng) -> f64 {
+        let mut total = 1.0;
+        for object in &self.objects {
+            total *= object.transmittance(ray, t_min, t_max, rng);
+            if total <= 0.0 {
+                break;
+            }
+        }
+        total
+    }
 }
\ No newline at end of file
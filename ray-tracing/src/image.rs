@@ -0,0 +1,744 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::color::{self, Color};
+use crate::constants::MAX_IMAGE_SIDE;
+use crate::deflate;
+use crate::exr::{self, ExrCompression, ExrPixel};
+use crate::render::Framebuffer;
+use crate::tonemap::PostProcess;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ImageFormat {
+    P3,  // PPM texte, un pixel par ligne
+    P6,  // PPM binaire
+    Png,
+    Pfm, // radiance linéaire en f32, avant tone mapping
+    Exr { pixel: ExrPixel, compression: ExrCompression },
+}
+
+impl ImageFormat {
+    pub fn from_name(name: &str) -> Option<ImageFormat> {
+        match name.to_ascii_lowercase().as_str() {
+            "p3" => Some(ImageFormat::P3),
+            "p6" | "ppm" => Some(ImageFormat::P6),
+            "png" => Some(ImageFormat::Png),
+            "pfm" => Some(ImageFormat::Pfm),
+            "exr" => Some(ImageFormat::exr()),
+            _ => None,
+        }
+    }
+
+    // OpenEXR par défaut : demi-flottants compressés en ZIP
+    fn exr() -> ImageFormat {
+        ImageFormat::Exr {
+            pixel: ExrPixel::Half,
+            compression: ExrCompression::Zip,
+        }
+    }
+
+    // Extension des fichiers écrits dans ce format (images d'une animation)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::P3 | ImageFormat::P6 => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Pfm => "pfm",
+            ImageFormat::Exr { .. } => "exr",
+        }
+    }
+
+    // Déduit le format de l'extension : .ppm donne du P6, .png du PNG, .pfm et .exr de la HDR
+    pub fn from_path(path: &Path) -> Option<ImageFormat> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "ppm" => Some(ImageFormat::P6),
+            "png" => Some(ImageFormat::Png),
+            "pfm" => Some(ImageFormat::Pfm),
+            "exr" => Some(ImageFormat::exr()),
+            _ => None,
+        }
+    }
+}
+
+// Convertit tout le framebuffer en octets RGB, lignes de haut en bas
+fn to_rgb8(fb: &Framebuffer, post: &PostProcess) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(fb.width() * fb.height() * 3);
+    for y in 0..fb.height() {
+        for x in 0..fb.width() {
+            bytes.extend_from_slice(&color::to_rgb8(post.apply(fb.get(x, y))));
+        }
+    }
+    bytes
+}
+
+// Le framebuffer (radiance linéaire) est développé par `post` puis encodé en sRGB 8 bits,
+// sauf pour PFM et OpenEXR qui reçoivent la radiance telle quelle
+pub fn write_image(path: &Path, fb: &Framebuffer, format: ImageFormat, post: &PostProcess) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    match format {
+        ImageFormat::P3 => write_p3(&mut out, fb.width(), fb.height(), &to_rgb8(fb, post))?,
+        ImageFormat::P6 => write_p6(&mut out, fb.width(), fb.height(), &to_rgb8(fb, post))?,
+        ImageFormat::Png => write_png(&mut out, fb.width(), fb.height(), &to_rgb8(fb, post))?,
+        ImageFormat::Pfm => write_pfm(&mut out, fb)?,
+        ImageFormat::Exr { pixel, compression } => exr::write_exr(&mut out, fb, pixel, compression)?,
+    }
+    out.flush()
+}
+
+fn write_p3(out: &mut impl Write, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    writeln!(out, "P3\n{} {}\n255", width, height)?;
+    for pixel in rgb.chunks(3) {
+        writeln!(out, "{} {} {}", pixel[0], pixel[1], pixel[2])?;
+    }
+    Ok(())
+}
+
+fn write_p6(out: &mut impl Write, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    out.write_all(rgb)
+}
+
+// PFM petit-boutiste (échelle -1), lignes de bas en haut comme l'impose le format
+fn write_pfm(out: &mut impl Write, fb: &Framebuffer) -> io::Result<()> {
+    write!(out, "PF\n{} {}\n-1.0\n", fb.width(), fb.height())?;
+    let mut data = Vec::with_capacity(fb.width() * fb.height() * 12);
+    for y in (0..fb.height()).rev() {
+        for x in 0..fb.width() {
+            let c = fb.get(x, y);
+            for v in [c.x(), c.y(), c.z()] {
+                data.extend_from_slice(&(v as f32).to_le_bytes());
+            }
+        }
+    }
+    out.write_all(&data)
+}
+
+fn png_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    // Le CRC couvre le type et les données
+    let mut crc_input = Vec::with_capacity(4 + data.len());
+    crc_input.extend_from_slice(kind);
+    crc_input.extend_from_slice(data);
+    out.write_all(&deflate::crc32(&crc_input).to_be_bytes())
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+// Chaque ligne est précédée de son type de filtre : on garde celui dont la somme
+// des valeurs absolues est la plus faible (heuristique recommandée par la norme PNG)
+fn filter_rows(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    const BPP: usize = 3;
+    let stride = width * BPP;
+    let zero_row = vec![0u8; stride];
+    let mut out = Vec::with_capacity((stride + 1) * height);
+    let mut candidate = vec![0u8; stride];
+
+    for y in 0..height {
+        let row = &rgb[y * stride..(y + 1) * stride];
+        let up = if y > 0 { &rgb[(y - 1) * stride..y * stride] } else { &zero_row[..] };
+
+        let mut best_filter = 0;
+        let mut best_row = row.to_vec();
+        let mut best_score: u64 = row.iter().map(|&v| (v as i8).unsigned_abs() as u64).sum();
+        for filter in 1..=4u8 {
+            for i in 0..stride {
+                let a = if i >= BPP { row[i - BPP] } else { 0 };
+                let b = up[i];
+                let c = if i >= BPP { up[i - BPP] } else { 0 };
+                let predicted = match filter {
+                    1 => a,
+                    2 => b,
+                    3 => ((a as u16 + b as u16) / 2) as u8,
+                    _ => paeth(a, b, c),
+                };
+                candidate[i] = row[i].wrapping_sub(predicted);
+            }
+            let score: u64 = candidate.iter().map(|&v| (v as i8).unsigned_abs() as u64).sum();
+            if score < best_score {
+                best_score = score;
+                best_filter = filter;
+                best_row.copy_from_slice(&candidate);
+            }
+        }
+        out.push(best_filter);
+        out.extend_from_slice(&best_row);
+    }
+    out
+}
+
+fn write_png(out: &mut impl Write, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // 8 bits par canal, RGB, compression deflate, filtrage standard, pas d'entrelacement
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+    png_chunk(out, b"IHDR", &header)?;
+
+    let data = deflate::zlib_compress(&filter_rows(width, height, rgb));
+    png_chunk(out, b"IDAT", &data)?;
+    png_chunk(out, b"IEND", &[])
+}
+
+// Image lue depuis un fichier, lignes de haut en bas. Les composantes sont telles qu'encodées :
+// dans [0, 1] et non linéaires pour PPM / PNG (textures), radiance linéaire non bornée pour .hdr / PFM
+pub struct Picture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Color>,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// Nombre de pixels annoncé par un en-tête, None si un côté est nul ou démesuré
+fn pixel_count(width: usize, height: usize) -> Option<usize> {
+    if width == 0 || height == 0 || width > MAX_IMAGE_SIDE || height > MAX_IMAGE_SIDE {
+        return None;
+    }
+    width.checked_mul(height)
+}
+
+// Lit un PPM (P3 ou P6) ou un PNG, reconnus à leur signature et non à l'extension
+pub fn read_image(path: &Path) -> io::Result<Picture> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        read_png(&bytes)
+    } else if bytes.starts_with(b"P3") || bytes.starts_with(b"P6") {
+        read_ppm(&bytes)
+    } else {
+        Err(invalid("unsupported image format, expected PPM (P3/P6) or PNG"))
+    }
+}
+
+// Lit une image HDR : Radiance (.hdr, RGBE) ou PFM, reconnues à leur signature
+pub fn read_hdr_image(path: &Path) -> io::Result<Picture> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(b"#?") {
+        read_rgbe(&bytes)
+    } else if bytes.starts_with(b"PF") || bytes.starts_with(b"Pf") {
+        read_pfm(&bytes)
+    } else {
+        Err(invalid("unsupported HDR format, expected Radiance .hdr or PFM"))
+    }
+}
+
+fn rgbe_to_color(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    // Mantisses sur 8 bits partageant un exposant commun
+    let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    Color::new(rgbe[0] as f64 * f, rgbe[1] as f64 * f, rgbe[2] as f64 * f)
+}
+
+// Format Radiance : en-tête texte, ligne de résolution "-Y h +X w", puis lignes RGBE
+// compressées par plages (RLE "nouveau style", un canal après l'autre) ou brutes
+fn read_rgbe(bytes: &[u8]) -> io::Result<Picture> {
+    let mut pos = 0;
+    let next_line = |pos: &mut usize| -> io::Result<String> {
+        let start = *pos;
+        while *bytes.get(*pos).ok_or_else(|| invalid("truncated HDR header"))? != b'\n' {
+            *pos += 1;
+        }
+        *pos += 1;
+        Ok(String::from_utf8_lossy(&bytes[start..*pos - 1]).into_owned())
+    };
+    loop {
+        let line = next_line(&mut pos)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid("only 32-bit_rle_rgbe HDR files are supported"));
+            }
+        }
+    }
+    let resolution = next_line(&mut pos)?;
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", h, "+X", w] => (
+            h.parse::<usize>().map_err(|_| invalid("invalid HDR resolution"))?,
+            w.parse::<usize>().map_err(|_| invalid("invalid HDR resolution"))?,
+        ),
+        _ => return Err(invalid("unsupported HDR orientation, expected -Y h +X w")),
+    };
+    let count = pixel_count(width, height).ok_or_else(|| invalid("invalid HDR resolution"))?;
+    // Une ligne compressée occupe au moins 4 octets d'en-tête puis, pour chaque canal,
+    // 2 octets par plage de 127 pixels : de quoi refuser un fichier tronqué avant d'allouer
+    let rle_width = (8..32768).contains(&width);
+    let min_scanline = if rle_width { 4 + 8 * width.div_ceil(127) } else { 4 * width };
+    if bytes.len() - pos < min_scanline * height {
+        return Err(invalid("truncated HDR data"));
+    }
+
+    let byte = |pos: &mut usize| -> io::Result<u8> {
+        let b = *bytes.get(*pos).ok_or_else(|| invalid("truncated HDR data"))?;
+        *pos += 1;
+        Ok(b)
+    };
+    let mut pixels = Vec::with_capacity(count);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        let start = [byte(&mut pos)?, byte(&mut pos)?, byte(&mut pos)?, byte(&mut pos)?];
+        let rle = rle_width && start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0;
+        if rle {
+            if (start[2] as usize) << 8 | start[3] as usize != width {
+                return Err(invalid("HDR scanline width mismatch"));
+            }
+            for channel in 0..4 {
+                let mut x = 0;
+                while x < width {
+                    let count = byte(&mut pos)? as usize;
+                    if count > 128 {
+                        // Plage : une valeur répétée
+                        let value = byte(&mut pos)?;
+                        let run = count - 128;
+                        if x + run > width {
+                            return Err(invalid("corrupt HDR run length"));
+                        }
+                        for pixel in &mut scanline[x..x + run] {
+                            pixel[channel] = value;
+                        }
+                        x += run;
+                    } else {
+                        if count == 0 || x + count > width {
+                            return Err(invalid("corrupt HDR run length"));
+                        }
+                        for pixel in &mut scanline[x..x + count] {
+                            pixel[channel] = byte(&mut pos)?;
+                        }
+                        x += count;
+                    }
+                }
+            }
+        } else {
+            // Ligne non compressée : le premier pixel est déjà lu
+            scanline[0] = start;
+            for pixel in scanline.iter_mut().skip(1) {
+                *pixel = [byte(&mut pos)?, byte(&mut pos)?, byte(&mut pos)?, byte(&mut pos)?];
+            }
+        }
+        pixels.extend(scanline.iter().map(|&p| rgbe_to_color(p)));
+    }
+    Ok(Picture { width, height, pixels })
+}
+
+// PFM : "PF" (RGB) ou "Pf" (gris), dimensions, échelle (négative = petit-boutiste),
+// puis des f32 ligne par ligne, de bas en haut
+fn read_pfm(bytes: &[u8]) -> io::Result<Picture> {
+    let mut pos = 0;
+    let channels = if ppm_token(bytes, &mut pos)? == "PF" { 3 } else { 1 };
+    let width = ppm_number(bytes, &mut pos)?;
+    let height = ppm_number(bytes, &mut pos)?;
+    let scale: f64 = ppm_token(bytes, &mut pos)?
+        .parse()
+        .map_err(|_| invalid("invalid PFM scale"))?;
+    let count = pixel_count(width, height).ok_or_else(|| invalid("invalid PFM header"))?;
+    if scale == 0.0 || !scale.is_finite() {
+        return Err(invalid("invalid PFM header"));
+    }
+    let little_endian = scale < 0.0;
+
+    let data = bytes.get(pos + 1..).unwrap_or(&[]);
+    if count.checked_mul(channels * 4).is_none_or(|size| data.len() < size) {
+        return Err(invalid("truncated PFM file"));
+    }
+    let value = |index: usize| -> f64 {
+        let b = [data[4 * index], data[4 * index + 1], data[4 * index + 2], data[4 * index + 3]];
+        let v = if little_endian { f32::from_le_bytes(b) } else { f32::from_be_bytes(b) };
+        v as f64
+    };
+
+    let mut pixels = Vec::with_capacity(count);
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let first = (y * width + x) * channels;
+            pixels.push(if channels == 3 {
+                Color::new(value(first), value(first + 1), value(first + 2))
+            } else {
+                let g = value(first);
+                Color::new(g, g, g)
+            });
+        }
+    }
+    Ok(Picture { width, height, pixels })
+}
+
+// Mot suivant d'un en-tête PPM, en sautant blancs et commentaires
+fn ppm_token<'a>(bytes: &'a [u8], pos: &mut usize) -> io::Result<&'a str> {
+    loop {
+        match bytes.get(*pos) {
+            Some(b'#') => {
+                while bytes.get(*pos).is_some_and(|&c| c != b'\n') {
+                    *pos += 1;
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => *pos += 1,
+            Some(_) => break,
+            None => return Err(invalid("truncated PPM file")),
+        }
+    }
+    let start = *pos;
+    while bytes.get(*pos).is_some_and(|c| !c.is_ascii_whitespace()) {
+        *pos += 1;
+    }
+    std::str::from_utf8(&bytes[start..*pos]).map_err(|_| invalid("invalid PPM header"))
+}
+
+fn ppm_number(bytes: &[u8], pos: &mut usize) -> io::Result<usize> {
+    ppm_token(bytes, pos)?
+        .parse()
+        .map_err(|_| invalid("invalid number in PPM file"))
+}
+
+fn read_ppm(bytes: &[u8]) -> io::Result<Picture> {
+    let mut pos = 0;
+    let binary = ppm_token(bytes, &mut pos)? == "P6";
+    let width = ppm_number(bytes, &mut pos)?;
+    let height = ppm_number(bytes, &mut pos)?;
+    let maxval = ppm_number(bytes, &mut pos)?;
+    let count = pixel_count(width, height)
+        .and_then(|n| n.checked_mul(3))
+        .ok_or_else(|| invalid("invalid PPM header"))?;
+    if maxval == 0 || maxval > 65535 {
+        return Err(invalid("invalid PPM header"));
+    }
+
+    // Au moins un octet par composante, même en texte : on vérifie avant d'allouer
+    let data = bytes.get(pos + 1..).unwrap_or(&[]);
+    let size = if binary && maxval >= 256 { 2 } else { 1 };
+    if data.len() < count * size {
+        return Err(invalid("truncated PPM file"));
+    }
+    let mut samples = Vec::with_capacity(count);
+    if binary {
+        // Un seul blanc sépare l'en-tête des données, puis 1 ou 2 octets par composante
+        for value in data.chunks(size).take(count) {
+            samples.push(if size == 1 { value[0] as usize } else { (value[0] as usize) << 8 | value[1] as usize });
+        }
+    } else {
+        for _ in 0..count {
+            samples.push(ppm_number(bytes, &mut pos)?);
+        }
+    }
+
+    let scale = 1.0 / maxval as f64;
+    Ok(Picture {
+        width,
+        height,
+        pixels: samples
+            .chunks(3)
+            .map(|c| Color::new(c[0] as f64 * scale, c[1] as f64 * scale, c[2] as f64 * scale))
+            .collect(),
+    })
+}
+
+// Inverse du filtrage fait à l'écriture, ligne par ligne (bpp : octets par pixel, au moins 1)
+fn unfilter_rows(data: &[u8], stride: usize, height: usize, bpp: usize) -> io::Result<Vec<u8>> {
+    if (stride + 1).checked_mul(height).is_none_or(|size| data.len() < size) {
+        return Err(invalid("truncated PNG image data"));
+    }
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = data[y * (stride + 1)];
+        let src = &data[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, rest) = out.split_at_mut(y * stride);
+        let up = if y > 0 { &done[(y - 1) * stride..] } else { &[][..] };
+        let row = &mut rest[..stride];
+        for i in 0..stride {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = up.get(i).copied().unwrap_or(0);
+            let c = if i >= bpp { up.get(i - bpp).copied().unwrap_or(0) } else { 0 };
+            let predicted = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(invalid("invalid PNG filter type")),
+            };
+            row[i] = src[i].wrapping_add(predicted);
+        }
+    }
+    Ok(out)
+}
+
+// PNG non entrelacé, toutes profondeurs et types de couleur ; la transparence est ignorée
+fn read_png(bytes: &[u8]) -> io::Result<Picture> {
+    let mut pos = 8;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    loop {
+        let chunk_header = bytes.get(pos..pos + 8).ok_or_else(|| invalid("truncated PNG file"))?;
+        let len = u32::from_be_bytes([chunk_header[0], chunk_header[1], chunk_header[2], chunk_header[3]]) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let body = bytes.get(pos + 4..pos + 12 + len).ok_or_else(|| invalid("truncated PNG file"))?;
+        let (typed, crc) = body.split_at(4 + len);
+        if deflate::crc32(typed) != u32::from_be_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+            return Err(invalid("PNG chunk CRC mismatch"));
+        }
+        let data = &typed[4..];
+        match kind {
+            b"IHDR" if len == 13 => header = Some(data),
+            b"PLTE" => palette = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + len;
+    }
+
+    let h = header.ok_or_else(|| invalid("missing PNG header"))?;
+    let width = u32::from_be_bytes([h[0], h[1], h[2], h[3]]) as usize;
+    let height = u32::from_be_bytes([h[4], h[5], h[6], h[7]]) as usize;
+    let (depth, color_type) = (h[8] as usize, h[9]);
+    if h[12] != 0 {
+        return Err(invalid("interlaced PNG is not supported"));
+    }
+    let channels = match color_type {
+        0 | 3 => 1, // gris, palette
+        2 => 3,     // RGB
+        4 => 2,     // gris + alpha
+        6 => 4,     // RGBA
+        _ => return Err(invalid("invalid PNG color type")),
+    };
+    let count = pixel_count(width, height).ok_or_else(|| invalid("invalid PNG header"))?;
+    if !matches!(depth, 1 | 2 | 4 | 8 | 16) {
+        return Err(invalid("invalid PNG header"));
+    }
+
+    let bits_per_pixel = channels * depth;
+    let stride = (width * bits_per_pixel).div_ceil(8);
+    let raw = deflate::zlib_decompress(&compressed).map_err(|e| invalid(&e))?;
+    let rows = unfilter_rows(&raw, stride, height, bits_per_pixel.div_ceil(8))?;
+
+    // Échantillon n° `index` d'une ligne, quelle que soit sa taille en bits
+    let sample = |row: &[u8], index: usize| -> usize {
+        match depth {
+            16 => (row[2 * index] as usize) << 8 | row[2 * index + 1] as usize,
+            8 => row[index] as usize,
+            _ => {
+                let bit = index * depth;
+                (row[bit / 8] as usize >> (8 - depth - bit % 8)) & ((1 << depth) - 1)
+            }
+        }
+    };
+    let scale = 1.0 / ((1usize << depth) - 1) as f64;
+
+    let mut pixels = Vec::with_capacity(count);
+    for row in rows.chunks(stride) {
+        for x in 0..width {
+            let first = x * channels;
+            let pixel = match color_type {
+                3 => {
+                    let i = sample(row, first) * 3;
+                    let rgb = palette.get(i..i + 3).ok_or_else(|| invalid("PNG palette index out of range"))?;
+                    Color::new(rgb[0] as f64, rgb[1] as f64, rgb[2] as f64) / 255.0
+                }
+                0 | 4 => {
+                    let g = sample(row, first) as f64 * scale;
+                    Color::new(g, g, g)
+                }
+                _ => Color::new(
+                    sample(row, first) as f64 * scale,
+                    sample(row, first + 1) as f64 * scale,
+                    sample(row, first + 2) as f64 * scale,
+                ),
+            };
+            pixels.push(pixel);
+        }
+    }
+    Ok(Picture { width, height, pixels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgb8(picture: &Picture) -> Vec<u8> {
+        picture
+            .pixels
+            .iter()
+            .flat_map(|c| [c.x(), c.y(), c.z()])
+            .map(|v| (v * 255.0).round() as u8)
+            .collect()
+    }
+
+    fn png_round_trip(width: usize, height: usize, rgb: &[u8]) {
+        let mut bytes = Vec::new();
+        write_png(&mut bytes, width, height, rgb).unwrap();
+        let picture = read_png(&bytes).unwrap();
+        assert_eq!((picture.width, picture.height), (width, height));
+        assert_eq!(rgb8(&picture), rgb);
+    }
+
+    // Encode une ligne RGBE comme Radiance : un canal après l'autre, plages d'au moins 3 valeurs
+    fn rle_scanline(out: &mut Vec<u8>, scanline: &[[u8; 4]]) {
+        out.extend_from_slice(&[2, 2, (scanline.len() >> 8) as u8, scanline.len() as u8]);
+        for channel in 0..4 {
+            let values: Vec<u8> = scanline.iter().map(|p| p[channel]).collect();
+            let mut x = 0;
+            while x < values.len() {
+                let run = values[x..].iter().take(127).take_while(|&&v| v == values[x]).count();
+                if run >= 3 {
+                    out.extend_from_slice(&[128 + run as u8, values[x]]);
+                    x += run;
+                } else {
+                    let literal = (values.len() - x).min(128);
+                    out.push(literal as u8);
+                    out.extend_from_slice(&values[x..x + literal]);
+                    x += literal;
+                }
+            }
+        }
+    }
+
+    fn hdr_header(width: usize, height: usize) -> Vec<u8> {
+        format!("#?RADIANCE\n# essai\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width).into_bytes()
+    }
+
+    #[test]
+    fn rgbe_decodes_shared_exponent() {
+        let c = rgbe_to_color([128, 64, 32, 129]);
+        assert_eq!((c.x(), c.y(), c.z()), (1.0, 0.5, 0.25));
+        assert_eq!(rgbe_to_color([200, 10, 10, 0]).x(), 0.0);
+    }
+
+    #[test]
+    fn rgbe_rle_round_trip() {
+        // Plages longues, valeurs isolées, et une plage plus longue que 127
+        let (width, height) = (300, 3);
+        let mut expected = Vec::new();
+        let mut bytes = hdr_header(width, height);
+        for y in 0..height {
+            let scanline: Vec<[u8; 4]> = (0..width)
+                .map(|x| {
+                    let e = if x < 200 { 128 } else { 130 + y as u8 };
+                    [(x * 7 % 256) as u8, (x / 50 * 40) as u8, (y * 90) as u8, e]
+                })
+                .collect();
+            rle_scanline(&mut bytes, &scanline);
+            expected.extend(scanline.iter().map(|&p| rgbe_to_color(p)));
+        }
+        let picture = read_rgbe(&bytes).unwrap();
+        assert_eq!((picture.width, picture.height), (width, height));
+        assert!(picture.pixels.iter().zip(&expected).all(|(a, b)| a.x() == b.x() && a.y() == b.y() && a.z() == b.z()));
+
+        // Une plage qui dépasse la ligne, ou des données tronquées, sont refusées
+        let mut corrupt = hdr_header(8, 1);
+        corrupt.extend_from_slice(&[2, 2, 0, 8, 128 + 9, 1]);
+        assert!(read_rgbe(&corrupt).is_err());
+        assert!(read_rgbe(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn rgbe_flat_scanlines() {
+        // Moins de 8 pixels : les lignes ne sont jamais compressées
+        let mut bytes = hdr_header(2, 1);
+        bytes.extend_from_slice(&[128, 0, 0, 129, 0, 128, 0, 128]);
+        let picture = read_rgbe(&bytes).unwrap();
+        assert_eq!(picture.pixels[0].x(), 1.0);
+        assert_eq!(picture.pixels[1].y(), 0.5);
+    }
+
+    #[test]
+    fn oversized_hdr_and_pfm_headers() {
+        // Résolution démesurée, ou sans les données qu'elle annonce : erreur, pas d'allocation
+        let mut hdr = hdr_header(1_000_000_000_000_000, 1);
+        assert_eq!(read_rgbe(&hdr).err().unwrap().to_string(), "invalid HDR resolution");
+        hdr = hdr_header(30_000, 30_000);
+        hdr.extend_from_slice(&[2, 2, 0x75, 0x30, 128 + 127, 1]);
+        assert_eq!(read_rgbe(&hdr).err().unwrap().to_string(), "truncated HDR data");
+
+        let error = read_pfm(b"PF\n4611686018427387904 4\n-1.0\n").err().unwrap();
+        assert_eq!(error.to_string(), "invalid PFM header");
+        assert!(read_pfm(b"PF\n60000 60000\n-1.0\n").is_err());
+        let mut pfm = b"Pf\n1 1\n-1.0\n".to_vec();
+        pfm.extend_from_slice(&0.5f32.to_le_bytes());
+        assert_eq!(read_pfm(&pfm).unwrap().pixels[0].y(), 0.5);
+    }
+
+    #[test]
+    fn oversized_ppm_and_png_headers() {
+        // Le produit des côtés déborderait : l'en-tête est refusé avant toute allocation
+        let error = read_ppm(b"P6 4611686018427387904 4 255\n").err().unwrap();
+        assert_eq!(error.to_string(), "invalid PPM header");
+        assert!(read_ppm(b"P3 70000 1 255\n").is_err());
+        assert!(read_ppm(b"P3 2 1 255\n1 2 3\n").is_err());
+        assert_eq!(read_ppm(b"P3 1 1 255\n255 0 51\n").unwrap().pixels[0].z(), 0.2);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut header = ((MAX_IMAGE_SIDE + 1) as u32).to_be_bytes().to_vec();
+        header.extend_from_slice(&[0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        png_chunk(&mut png, b"IHDR", &header).unwrap();
+        png_chunk(&mut png, b"IEND", &[]).unwrap();
+        assert_eq!(read_png(&png).err().unwrap().to_string(), "invalid PNG header");
+    }
+
+    #[test]
+    fn png_round_trip_smallest() {
+        png_round_trip(1, 1, &[12, 200, 255]);
+    }
+
+    #[test]
+    fn png_round_trip_incompressible() {
+        // Bruit : chaque ligne choisit un filtre différent
+        let mut x = 0x9E37_79B9u32;
+        let rgb: Vec<u8> = (0..37 * 23 * 3)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                (x >> 24) as u8
+            })
+            .collect();
+        png_round_trip(37, 23, &rgb);
+    }
+
+    #[test]
+    fn png_round_trip_repetitive() {
+        let flat = [80u8, 160, 240].repeat(64 * 64);
+        png_round_trip(64, 64, &flat);
+
+        let gradient: Vec<u8> = (0..50 * 40).flat_map(|i| [(i % 50) as u8 * 5, (i / 50) as u8 * 6, 7]).collect();
+        png_round_trip(50, 40, &gradient);
+    }
+
+    #[test]
+    fn png_reads_reference_file() {
+        // 3x5 RGB écrit par zlib et Python, une ligne par type de filtre (0 à 4)
+        let hex = "89504e470d0a1a0a0000000d49484452000000030000000508020000000f13c1f50000003d4944415478da013200cdff0000254a6f94b9de0328010b30557a9fc4e90e3302163b6085aacff4193e0321466b90b5daff2449042c51769bc0e50a2f54c01312edad944f580000000049454e44ae426082";
+        let bytes: Vec<u8> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect();
+        let expected: [u8; 45] = [
+            0, 37, 74, 111, 148, 185, 222, 3, 40, 11, 48, 85, 133, 207, 25, 110, 221, 76, 33, 107, 181, 10, 121, 232, 98, 246,
+            138, 49, 123, 197, 173, 47, 176, 134, 182, 230, 93, 204, 59, 72, 59, 32, 82, 229, 116,
+        ];
+        let picture = read_png(&bytes).unwrap();
+        assert_eq!((picture.width, picture.height), (3, 5));
+        assert_eq!(rgb8(&picture), expected);
+
+        // Un octet modifié : le CRC du bloc le détecte
+        let mut corrupt = bytes.clone();
+        corrupt[40] ^= 0x10;
+        assert!(read_png(&corrupt).is_err());
+    }
+}
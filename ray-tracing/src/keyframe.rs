@@ -0,0 +1,119 @@
+use crate::vec3::Vec3;
+
+/*
+Piste d'animation : des valeurs clés datées, interpolées à n'importe quel instant.
+Avant la première clé et après la dernière, la valeur reste figée.
+Chaque clé choisit comment on va d'elle à la suivante : en ligne droite (linéaire), ou par une
+courbe de Catmull-Rom qui passe par les clés sans changer brusquement de vitesse.
+L'interpolation est une somme pondérée des clés voisines : tout type qui sait faire
+cette somme (nombre, vecteur, placement d'un objet) peut être animé.
+*/
+pub trait Keyable: Copy {
+    // Somme des valeurs multipliées par leurs poids (les poids font 1 au total)
+    fn weighted(values: &[(f64, Self)]) -> Self;
+}
+
+impl Keyable for f64 {
+    fn weighted(values: &[(f64, f64)]) -> f64 {
+        values.iter().map(|(w, v)| w * v).sum()
+    }
+}
+
+impl Keyable for Vec3 {
+    fn weighted(values: &[(f64, Vec3)]) -> Vec3 {
+        values.iter().fold(Vec3::default(), |sum, &(w, v)| sum + w * v)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Interpolation {
+    Linear,
+    CatmullRom,
+}
+
+impl Interpolation {
+    pub fn from_name(name: &str) -> Option<Interpolation> {
+        match name {
+            "linear" => Some(Interpolation::Linear),
+            "catmull_rom" | "catmull-rom" => Some(Interpolation::CatmullRom),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Track<T> {
+    keys: Vec<(f64, T)>,              // (instant, valeur), par instants strictement croissants
+    interpolation: Vec<Interpolation>, // de chaque clé vers la suivante
+}
+
+impl<T: Keyable> Track<T> {
+    pub fn new() -> Track<T> {
+        Track {
+            keys: Vec::new(),
+            interpolation: Vec::new(),
+        }
+    }
+
+    // Les clés arrivent dans l'ordre : false si `time` ne suit pas la dernière
+    pub fn add(&mut self, time: f64, value: T, interpolation: Interpolation) -> bool {
+        if self.keys.last().is_some_and(|&(last, _)| time <= last) {
+            return false;
+        }
+        self.keys.push((time, value));
+        self.interpolation.push(interpolation);
+        true
+    }
+
+    pub fn keys(&self) -> &[(f64, T)] {
+        &self.keys
+    }
+
+    // Valeur à l'instant `time`, interpolée entre les deux clés qui l'entourent
+    pub fn at(&self, time: f64) -> T {
+        // Nombre de clés au plus à `time` : la suivante est la première au-delà
+        let next = self.keys.partition_point(|&(t, _)| t <= time);
+        if next == 0 {
+            return self.keys[0].1;
+        }
+        if next == self.keys.len() {
+            return self.keys[next - 1].1;
+        }
+        let i = next - 1;
+        let (t0, v0) = self.keys[i];
+        let (t1, v1) = self.keys[next];
+        let f = (time - t0) / (t1 - t0);
+        match self.interpolation[i] {
+            Interpolation::Linear => T::weighted(&[(1.0 - f, v0), (f, v1)]),
+            Interpolation::CatmullRom => self.catmull_rom(i, f),
+        }
+    }
+
+    // Courbe d'Hermite entre les clés i et i + 1 : la vitesse en une clé est la pente entre
+    // ses deux voisines, divisée par leur écart de temps (les clés peuvent être inégalement espacées).
+    // Aux extrémités de la piste, la clé sert de voisine à elle-même
+    fn catmull_rom(&self, i: usize, f: f64) -> T {
+        let last = self.keys.len() - 1;
+        let (before, after) = (i.saturating_sub(1), (i + 2).min(last));
+        let time = |k: usize| self.keys[k].0;
+        let value = |k: usize| self.keys[k].1;
+
+        // Polynômes de base d'Hermite
+        let (f2, f3) = (f * f, f * f * f);
+        let h00 = 2.0 * f3 - 3.0 * f2 + 1.0;
+        let h10 = f3 - 2.0 * f2 + f;
+        let h01 = -2.0 * f3 + 3.0 * f2;
+        let h11 = f3 - f2;
+
+        // Les tangentes sont des différences de clés : tout s'écrit comme une somme pondérée
+        let dt = time(i + 1) - time(i);
+        let a = h10 * dt / (time(i + 1) - time(before));
+        let b = h11 * dt / (time(after) - time(i));
+        T::weighted(&[
+            (-a, value(before)),
+            (h00 - b, value(i)),
+            (h01 + a, value(i + 1)),
+            (b, value(after)),
+        ])
+    }
+}
@@ -0,0 +1,91 @@
+use crate::color::Color;
+use crate::vec3::{Point3, Vec3};
+
+/*
+Lampes analytiques : elles n'ont pas de surface, un rayon ne peut donc jamais les toucher par hasard.
+Chaque point éclairé les interroge directement (éclairage direct) puis lance un rayon d'ombre
+vers elles : les ombres sont nettes et sans bruit, même avec peu d'échantillons.
+*/
+#[derive(Copy, Clone)]
+pub enum Light {
+    // Ampoule : l'éclairement décroît avec le carré de la distance
+    Point { position: Point3, intensity: Color },
+    // Projecteur : une ampoule limitée à un cône, avec un bord adouci
+    Spot {
+        position: Point3,
+        direction: Vec3, // axe du cône, normalisé
+        intensity: Color,
+        cos_outer: f64, // au-delà : aucune lumière
+        cos_inner: f64, // en deçà : pleine intensité
+    },
+    // Soleil : lumière parallèle venant de l'infini, sans atténuation
+    Directional { direction: Vec3, intensity: Color },
+}
+
+// Ce qu'un point reçoit d'une lampe, avant le test d'ombre
+pub struct LightSample {
+    pub direction: Vec3, // du point vers la lampe, normalisée
+    pub distance: f64,   // longueur du rayon d'ombre (infinie pour le soleil)
+    pub intensity: Color,
+}
+
+impl Light {
+    // angle : demi-angle du cône en degrés, falloff : largeur du bord adouci en degrés
+    pub fn spot(position: Point3, direction: Vec3, intensity: Color, angle: f64, falloff: f64) -> Light {
+        let outer = angle.to_radians();
+        let inner = (angle - falloff).max(0.0).to_radians();
+        Light::Spot {
+            position,
+            direction: Vec3::unit_vector(direction),
+            intensity,
+            cos_outer: outer.cos(),
+            cos_inner: inner.cos(),
+        }
+    }
+
+    pub fn directional(direction: Vec3, intensity: Color) -> Light {
+        Light::Directional {
+            direction: Vec3::unit_vector(direction),
+            intensity,
+        }
+    }
+
+    pub fn sample(&self, p: Point3) -> LightSample {
+        match *self {
+            Light::Point { position, intensity } => {
+                let to_light = position - p;
+                let distance = to_light.vec_length();
+                LightSample {
+                    direction: to_light / distance,
+                    distance,
+                    intensity: intensity / (distance * distance),
+                }
+            }
+            Light::Spot { position, direction, intensity, cos_outer, cos_inner } => {
+                let to_light = position - p;
+                let distance = to_light.vec_length();
+                let l = to_light / distance;
+                // Transition douce (smoothstep) entre le bord du cône et sa partie pleine
+                let cos_theta = Vec3::dot(-l, direction);
+                let t = if cos_inner > cos_outer {
+                    ((cos_theta - cos_outer) / (cos_inner - cos_outer)).clamp(0.0, 1.0)
+                } else if cos_theta >= cos_outer {
+                    1.0
+                } else {
+                    0.0
+                };
+                let falloff = t * t * (3.0 - 2.0 * t);
+                LightSample {
+                    direction: l,
+                    distance,
+                    intensity: falloff / (distance * distance) * intensity,
+                }
+            }
+            Light::Directional { direction, intensity } => LightSample {
+                direction: -direction,
+                distance: f64::INFINITY,
+                intensity,
+            },
+        }
+    }
+}
@@ -12,9 +12,59 @@ mod plane;
 mod cube;
 mod cylinder;
 mod constants;
+mod render;
+mod aabb;
+mod bvh;
+mod scene;
+mod cli;
+mod image;
+mod deflate;
+mod background;
+mod triangle;
+mod mesh;
+mod obj;
+mod matrix;
+mod transform;
+mod texture;
+mod perlin;
+mod light;
+mod pdf;
+mod quad;
+mod envmap;
+mod tonemap;
+mod exr;
+mod csg;
+mod medium;
+mod voxel;
+mod keyframe;
+mod moving_sphere;
+mod animation;
 
-use tuto_draw::draw_img;
+use std::env;
+use std::process;
+
+use cli::Command;
+use tuto_draw::{draw_animation, draw_img};
 
 fn main() {
-    let _ = draw_img();
-}
\ No newline at end of file
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Render(options)) => options,
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    let result = match &options.frames {
+        Some(dir) => draw_animation(&options, dir),
+        None => draw_img(&options),
+    };
+    if let Err(e) = result {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
@@ -1,62 +1,199 @@
+use std::sync::Arc;
+
 use crate::color::Color;
+use crate::common::{self, RenderRng, PI};
 use crate::hittable::HitRecord;
+use crate::pdf::Pdf;
 use crate::ray::Ray;
+use crate::texture::{SolidColor, Texture};
 use crate::vec3::Vec3;
  
-pub trait Material {
-    fn scatter(
-        &self,
-        r_in: &Ray,
-        rec: &HitRecord,
-        attenuation: &mut Color,
-        scattered: &mut Ray,
-    ) -> bool;
+// Résultat d'une diffusion : une surface diffuse donne une densité de directions (pdf),
+// combinable avec l'échantillonnage des lampes ; un miroir ou du verre donne un seul rayon
+#[derive(Default)]
+pub struct ScatterRecord {
+    pub pdf: Option<Pdf>,      // Some : direction à tirer selon pdf, contribution donnée par eval
+    pub attenuation: Color,    // sinon : couleur du rayon spéculaire
+    pub specular_ray: Ray,
+}
+
+pub trait Material: Send + Sync {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, srec: &mut ScatterRecord, rng: &mut RenderRng) -> bool;
+
+    // Lumière émise par la surface elle-même, noire pour tout ce qui n'est pas une lampe
+    fn emitted(&self, _rec: &HitRecord) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    // Part de la lumière arrivant de `direction` (normalisée) renvoyée vers le rayon incident : BRDF x cos.
+    // Sert à l'éclairage direct et aux rebonds tirés selon pdf ; nulle pour le verre et les miroirs
+    fn eval(&self, _r_in: &Ray, _rec: &HitRecord, _direction: Vec3) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
+
+    // Vrai pour les surfaces émissives : posées sur une sphère, un quad ou un triangle,
+    // elles deviennent des lampes surfaciques échantillonnées directement
+    fn is_light(&self) -> bool {
+        false
+    }
 }
 
 pub struct Lambertian {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(a: Color) -> Lambertian {
-        Lambertian { albedo: a}
+        Lambertian::textured(Arc::new(SolidColor::new(a)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Lambertian {
+        Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _r_in: &Ray, rec : &HitRecord, attenuation: &mut Color, scattered: &mut Ray,) -> bool {
-        let mut scatter_direction = rec.normal + Vec3::random_unit_vector();
-        
-        // Catch degenerate scatter direction
-        if scatter_direction.near_zero() {
-            scatter_direction = rec.normal;
-        }
-
-        *attenuation =  self.albedo;
-        *scattered = Ray::new(rec.p, scatter_direction);
+    fn scatter(&self, _r_in: &Ray, rec: &HitRecord, srec: &mut ScatterRecord, _rng: &mut RenderRng) -> bool {
+        // Directions réparties selon le cosinus autour de la normale
+        srec.pdf = Some(Pdf::cosine(rec.normal));
         true
     }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, direction: Vec3) -> Color {
+        // Diffusion uniforme : albedo / PI, pondérée par le cosinus d'incidence
+        let cosine = Vec3::dot(rec.normal, direction).max(0.0);
+        cosine / PI * self.albedo.value(rec.u, rec.v, &rec.p)
+    }
 }
 
 pub struct Metal {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
     fuzz: f64
 }
 
 impl Metal {
     pub fn new(a: Color, f: f64) -> Metal {
-        Metal { 
-            albedo: a,
+        Metal::textured(Arc::new(SolidColor::new(a)), f)
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>, f: f64) -> Metal {
+        Metal {
+            albedo,
             fuzz: if f < 1.0 {f} else {1.0},
         }
     }
 }
 
 impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord, attenuation: &mut Color, scattered: &mut Ray,) -> bool {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, srec: &mut ScatterRecord, rng: &mut RenderRng) -> bool {
         let reflected = Vec3::reflect(Vec3::unit_vector(r_in.direction()), rec.normal);
-        *attenuation = self.albedo;
-        *scattered = Ray::new(rec.p, reflected + self.fuzz * Vec3::random_in_unit_sphere());
-        Vec3::dot(scattered.direction(), rec.normal) > 0.0
+        srec.attenuation = self.albedo.value(rec.u, rec.v, &rec.p);
+        srec.specular_ray = Ray::new(rec.p, reflected + self.fuzz * Vec3::random_in_unit_sphere(rng), r_in.time());
+        Vec3::dot(srec.specular_ray.direction(), rec.normal) > 0.0
+    }
+}
+
+pub struct Dielectric {
+    ir: f64, // Index of Refraction
+}
+
+impl Dielectric {
+    pub fn new(index_of_refraction: f64) -> Dielectric {
+        Dielectric {
+            ir: index_of_refraction,
+        }
+    }
+
+    fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
+        // Use Schlick's approximation for reflectance
+        let r0 = ((1.0 - ref_idx) / (1.0 + ref_idx)).powi(2);
+        r0 + (1.0 - r0) * f64::powi(1.0 - cosine, 5)
+    }
+}
+
+impl Material for Dielectric {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, srec: &mut ScatterRecord, rng: &mut RenderRng) -> bool {
+        // Le verre n'absorbe rien
+        srec.attenuation = Color::new(1.0, 1.0, 1.0);
+        // On entre dans le matériau par la face avant, on en sort par la face arrière
+        let refraction_ratio = if rec.front_face { 1.0 / self.ir } else { self.ir };
+
+        let unit_direction = Vec3::unit_vector(r_in.direction());
+        let cos_theta = f64::min(Vec3::dot(-unit_direction, rec.normal), 1.0);
+        let sin_theta = f64::sqrt(1.0 - cos_theta * cos_theta);
+
+        // Réflexion totale interne quand Snell n'a pas de solution
+        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+        let direction = if cannot_refract
+            || Self::reflectance(cos_theta, refraction_ratio) > common::random_double(rng)
+        {
+            Vec3::reflect(unit_direction, rec.normal)
+        } else {
+            Vec3::refract(unit_direction, rec.normal, refraction_ratio)
+        };
+
+        srec.specular_ray = Ray::new(rec.p, direction, r_in.time());
+        true
+    }
+}
+
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> DiffuseLight {
+        DiffuseLight::textured(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn textured(emit: Arc<dyn Texture>) -> DiffuseLight {
+        DiffuseLight { emit }
     }
-}
\ No newline at end of file
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord, _srec: &mut ScatterRecord, _rng: &mut RenderRng) -> bool {
+        // Une lampe absorbe la lumière qu'elle reçoit
+        false
+    }
+
+    fn is_light(&self) -> bool {
+        true
+    }
+
+    fn emitted(&self, rec: &HitRecord) -> Color {
+        // Une lampe n'éclaire que du côté de sa normale (l'extérieur d'une sphère, le dessous d'un plafonnier)
+        if !rec.front_face {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        self.emit.value(rec.u, rec.v, &rec.p)
+    }
+}
+
+// Fonction de phase isotrope : un milieu (fumée, brouillard) renvoie la lumière dans toutes les
+// directions avec la même probabilité, quelle que soit la direction d'arrivée
+pub struct Isotropic {
+    albedo: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Isotropic {
+        Isotropic::textured(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn textured(albedo: Arc<dyn Texture>) -> Isotropic {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord, srec: &mut ScatterRecord, _rng: &mut RenderRng) -> bool {
+        srec.pdf = Some(Pdf::Uniform);
+        true
+    }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, _direction: Vec3) -> Color {
+        // Pas de surface, donc pas de cosinus : albedo réparti sur toute la sphère
+        self.albedo.value(rec.u, rec.v, &rec.p) / (4.0 * PI)
+    }
+}
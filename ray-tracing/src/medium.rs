@@ -0,0 +1,225 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::color::Color;
+use crate::common::{self, RenderRng};
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::{Isotropic, Material};
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use crate::voxel::VoxelGrid;
+
+/*
+Milieu de densité constante (fumée, brume, eau trouble) enfermé dans un solide fermé.
+Sur chaque portion du rayon à l'intérieur du solide, la distance parcourue avant une collision
+suit une loi exponentielle : -ln(ξ) / densité. Si elle tombe avant la sortie, le rayon est
+"touché" en ce point et diffusé par la fonction de phase (matériau Isotropic).
+ξ vient du générateur du pixel, reçu par hit_sampled : chaque passage du même rayon fait un
+nouveau tirage. hit, sans générateur, ne voit pas le milieu (il n'a pas de surface).
+*/
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable>,
+    density: f64,
+    phase: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    // None si la frontière n'est pas un solide fermé
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, phase: Arc<dyn Material>) -> Option<ConstantMedium> {
+        if !boundary.is_solid() {
+            return None;
+        }
+        Some(ConstantMedium { boundary, density, phase })
+    }
+}
+
+// Distance parcourue dans un milieu de densité `density` (ou un majorant) avant une collision
+fn free_path(density: f64, rng: &mut RenderRng) -> f64 {
+    -(1.0 - common::random_double(rng)).ln() / density
+}
+
+// Point de diffusion dans un milieu : pas de surface, la normale n'a pas de sens
+fn medium_record(r: &Ray, t: f64, phase: &Arc<dyn Material>, rec: &mut HitRecord) {
+    rec.t = t;
+    rec.p = r.at(t);
+    rec.normal = Vec3::new(1.0, 0.0, 0.0);
+    rec.front_face = true;
+    (rec.u, rec.v) = (0.0, 0.0);
+    rec.mat = Some(phase.clone());
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, _r: &Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord) -> bool {
+        false
+    }
+
+    fn hit_sampled(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, rng: &mut RenderRng) -> bool {
+        let speed = r.direction().vec_length();
+        for interval in self.boundary.intervals(r) {
+            // Portion de l'intérieur comprise dans [t_min, t_max]
+            let start = interval.enter.t.max(t_min);
+            let end = interval.exit.t.min(t_max);
+            if start >= end {
+                continue;
+            }
+            let distance = free_path(self.density, rng);
+            if distance < (end - start) * speed {
+                medium_record(r, start + distance / speed, &self.phase, rec);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+
+    // Densité constante : la transmittance est connue exactement, exp(-densité * longueur traversée)
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64, _rng: &mut RenderRng) -> f64 {
+        let mut length = 0.0;
+        for interval in self.boundary.intervals(r) {
+            length += (interval.exit.t.min(t_max) - interval.enter.t.max(t_min)).max(0.0);
+        }
+        (-self.density * length * r.direction().vec_length()).exp()
+    }
+}
+
+/*
+Milieu hétérogène : la densité vient d'une grille de voxels étirée sur une boîte alignée.
+La distance de collision n'a plus de formule, on la trouve par "delta tracking" :
+on avance par pas exponentiels tirés avec la densité maximale σmax (le majorant), comme si
+le milieu était uniformément dense, puis chaque collision est réelle avec la probabilité σ(p) / σmax.
+Les autres sont des collisions "nulles" : le rayon continue tout droit.
+Pour les rayons d'ombre, le "ratio tracking" fait les mêmes pas mais, au lieu de décider,
+multiplie la transmittance par 1 - σ(p) / σmax : moins de bruit qu'une réponse tout ou rien.
+Comme pour ConstantMedium, les pas sont tirés avec le générateur du pixel.
+*/
+pub struct GridMedium {
+    grid: VoxelGrid,
+    bbox: Aabb,
+    density: f64,  // multiplie les valeurs de la grille
+    majorant: f64, // σmax = density * plus grande valeur de la grille
+    phase: Arc<dyn Material>,
+}
+
+impl GridMedium {
+    pub fn new(grid: VoxelGrid, min: Point3, max: Point3, density: f64, phase: Arc<dyn Material>) -> GridMedium {
+        let majorant = density * grid.max();
+        GridMedium {
+            grid,
+            bbox: Aabb::new(min, max),
+            density,
+            majorant,
+            phase,
+        }
+    }
+
+    // Densité au point p (dans la boîte), ramené aux coordonnées [0, 1]^3 de la grille
+    fn density_at(&self, p: Point3) -> f64 {
+        let (min, max) = (self.bbox.min(), self.bbox.max());
+        let local = Vec3::new(
+            (p.x() - min.x()) / (max.x() - min.x()),
+            (p.y() - min.y()) / (max.y() - min.y()),
+            (p.z() - min.z()) / (max.z() - min.z()),
+        );
+        self.density * self.grid.sample(local)
+    }
+
+    // Portion du rayon dans la boîte, et vitesse du rayon (t est ramené à la distance parcourue)
+    fn segment(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+        if self.majorant <= 0.0 {
+            return None;
+        }
+        let (start, end) = self.bbox.clip(r, t_min, t_max)?;
+        Some((start, end, r.direction().vec_length()))
+    }
+}
+
+impl Hittable for GridMedium {
+    fn hit(&self, _r: &Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord) -> bool {
+        false
+    }
+
+    fn hit_sampled(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, rng: &mut RenderRng) -> bool {
+        let Some((start, end, speed)) = self.segment(r, t_min, t_max) else {
+            return false;
+        };
+        let mut t = start;
+        loop {
+            t += free_path(self.majorant, rng) / speed;
+            if t >= end {
+                return false;
+            }
+            if common::random_double(rng) * self.majorant < self.density_at(r.at(t)) {
+                medium_record(r, t, &self.phase, rec);
+                return true;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64, rng: &mut RenderRng) -> f64 {
+        let Some((start, end, speed)) = self.segment(r, t_min, t_max) else {
+            return 1.0;
+        };
+        let mut transmittance = 1.0;
+        let mut t = start;
+        loop {
+            t += free_path(self.majorant, rng) / speed;
+            if t >= end || transmittance <= 0.0 {
+                return transmittance;
+            }
+            transmittance *= 1.0 - self.density_at(r.at(t)) / self.majorant;
+        }
+    }
+}
+
+/*
+Brouillard homogène dans toute la scène, appliqué par ray_color entre deux surfaces :
+absorption et diffusion sont des coefficients par unité de distance.
+Un rayon qui ne touche rien (ciel, soleil directionnel) n'est pas atténué : le brouillard
+voile les objets lointains sans éteindre l'arrière-plan.
+*/
+pub struct Fog {
+    extinction: f64, // absorption + diffusion
+    phase: Arc<dyn Material>,
+}
+
+impl Fog {
+    // None si le brouillard n'a aucun effet
+    pub fn new(absorption: f64, scattering: f64) -> Option<Fog> {
+        let extinction = absorption + scattering;
+        if extinction <= 0.0 {
+            return None;
+        }
+        // Une collision sur extinction / diffusion renvoie de la lumière, les autres l'absorbent
+        let albedo = scattering / extinction;
+        Some(Fog {
+            extinction,
+            phase: Arc::new(Isotropic::new(Color::new(albedo, albedo, albedo))),
+        })
+    }
+
+    // Part de la lumière qui traverse `distance` de brouillard sans collision
+    pub fn transmittance(&self, distance: f64) -> f64 {
+        if distance.is_finite() {
+            (-self.extinction * distance).exp()
+        } else {
+            1.0
+        }
+    }
+
+    // Si le rayon qui allait toucher la surface `rec` heurte le brouillard avant,
+    // rec devient ce point de collision
+    pub fn scatter_before(&self, r: &Ray, rec: &mut HitRecord, rng: &mut RenderRng) {
+        let speed = r.direction().vec_length();
+        let distance = free_path(self.extinction, rng);
+        if distance < rec.t * speed {
+            medium_record(r, distance / speed, &self.phase, rec);
+        }
+    }
+}
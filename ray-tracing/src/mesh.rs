@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::triangle;
+use crate::vec3::{Point3, Vec3};
+
+// Une face référence ses sommets par indice dans les tableaux partagés du maillage
+#[derive(Clone)]
+pub struct Face {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    pub material: usize, // indice dans Mesh::materials
+}
+
+/*
+Maillage de triangles : sommets, normales et UV sont stockés une seule fois et partagés par les faces.
+Les triangles ne sont pas des Box<dyn Hittable> : le maillage possède sa propre BVH
+sur les indices de faces, et se présente à la scène comme un seul objet.
+*/
+pub struct Mesh {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<[f64; 2]>,
+    faces: Vec<Face>,
+    materials: Vec<Arc<dyn Material>>,
+    bvh: Bvh,
+}
+
+impl Mesh {
+    // Les indices des faces doivent être valides (vérifiés par le chargeur)
+    pub fn new(
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<[f64; 2]>,
+        faces: Vec<Face>,
+        materials: Vec<Arc<dyn Material>>,
+    ) -> Mesh {
+        let bounds: Vec<Aabb> = faces
+            .iter()
+            .map(|f| triangle::bounding_box(&f.positions.map(|i| positions[i])))
+            .collect();
+        Mesh {
+            bvh: Bvh::new(&bounds),
+            positions,
+            normals,
+            uvs,
+            faces,
+            materials,
+        }
+    }
+
+    fn hit_face(&self, index: usize, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let face = &self.faces[index];
+        let p = face.positions.map(|i| self.positions[i]);
+        let Some(hit) = triangle::intersect(r, &p, t_min, t_max) else {
+            return false;
+        };
+        let normals = face.normals.map(|n| n.map(|i| self.normals[i]));
+        let uvs = face.uvs.map(|t| t.map(|i| self.uvs[i]));
+        triangle::fill_record(r, rec, hit, &p, normals.as_ref(), uvs.as_ref(), &self.materials[face.material]);
+        true
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.bvh
+            .hit(r, t_min, t_max, rec, |i, t_max, rec| self.hit_face(i, r, t_min, t_max, rec))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
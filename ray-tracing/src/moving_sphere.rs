@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::hittable::{self, HitRecord, Hittable, Interval};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::sphere;
+use crate::vec3::{Point3, Vec3};
+
+/*
+Sphère en mouvement rectiligne : son centre va de center0 (à l'instant time0) à center1 (à time1),
+et reste immobile avant et après.
+Chaque rayon la voit à la position de son propre instant : pendant que l'obturateur de la caméra
+est ouvert, elle laisse une traînée floue.
+*/
+pub struct MovingSphere {
+    center0: Point3,
+    center1: Point3,
+    time0: f64,
+    time1: f64,
+    radius: f64,
+    mat: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    pub fn new(center0: Point3, center1: Point3, time0: f64, time1: f64, radius: f64, mat: Arc<dyn Material>) -> MovingSphere {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            mat,
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+        let f = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + f * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        sphere::hit_sphere(self.center(r.time()), self.radius, &self.mat, r, t_min, t_max, rec)
+    }
+
+    // Le mouvement est rectiligne : les boîtes des deux extrémités couvrent tout le trajet
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        let box0 = Aabb::new(self.center0 - r, self.center0 + r);
+        let box1 = Aabb::new(self.center1 - r, self.center1 + r);
+        Some(Aabb::surrounding_box(box0, box1))
+    }
+
+    fn is_solid(&self) -> bool {
+        true
+    }
+
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        hittable::convex_interval(self, r)
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::color::Color;
+use crate::image;
+use crate::material::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::mesh::{Face, Mesh};
+use crate::texture::{ImageTexture, Texture};
+use crate::vec3::{Point3, Vec3};
+
+/*
+Import de fichiers Wavefront OBJ et de leurs bibliothèques MTL.
+OBJ : v, vn, vt, f (polygones découpés en éventail), g / o, usemtl, mtllib.
+MTL : newmtl, Kd, Ks, Ns, Ni, d / Tr, Ke, illum, map_Kd, convertis vers les matériaux du projet :
+    Ke non nul             -> DiffuseLight
+    illum 4, 6, 7 ou d < 1 -> Dielectric (indice Ni)
+    illum 3 ou 5           -> Metal (couleur Ks, flou déduit de Ns)
+    sinon                  -> Lambertian (image map_Kd si présente, sinon couleur Kd)
+Les autres instructions (s, l, map_Ks, bump, ...) sont ignorées.
+*/
+
+#[derive(Debug)]
+pub struct ObjError {
+    pub file: PathBuf,
+    pub line: usize, // 0 quand l'erreur ne concerne pas une ligne précise
+    pub message: String,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if self.line > 0 {
+            write!(f, ":{}", self.line)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Error for ObjError {}
+
+pub struct ObjModel {
+    pub mesh: Mesh,
+    pub groups: Vec<String>,
+    pub triangles: usize,
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|e| ObjError {
+        file: path.to_path_buf(),
+        line: 0,
+        message: e.to_string(),
+    })
+}
+
+// Ligne en cours d'analyse, pour produire des erreurs localisées
+struct Line<'a> {
+    file: &'a Path,
+    number: usize,
+}
+
+impl Line<'_> {
+    fn error(&self, message: String) -> ObjError {
+        ObjError {
+            file: self.file.to_path_buf(),
+            line: self.number,
+            message,
+        }
+    }
+
+    fn numbers<const N: usize>(&self, keyword: &str, args: &[&str]) -> Result<[f64; N], ObjError> {
+        if args.len() < N {
+            return Err(self.error(format!("'{}' expects {} numbers", keyword, N)));
+        }
+        let mut out = [0.0; N];
+        for (value, text) in out.iter_mut().zip(args) {
+            *value = text
+                .parse()
+                .map_err(|_| self.error(format!("invalid number '{}' in '{}'", text, keyword)))?;
+        }
+        Ok(out)
+    }
+
+    // Indice OBJ (1 = premier élément, -1 = dernier lu) vers indice Rust
+    fn index(&self, text: &str, count: usize, what: &str) -> Result<usize, ObjError> {
+        let i: i64 = text
+            .parse()
+            .map_err(|_| self.error(format!("invalid {} index '{}'", what, text)))?;
+        let resolved = if i > 0 { i - 1 } else { count as i64 + i };
+        if i == 0 || resolved < 0 || resolved >= count as i64 {
+            return Err(self.error(format!("{} index {} out of range (1..={})", what, i, count)));
+        }
+        Ok(resolved as usize)
+    }
+}
+
+// Valeurs lues pour un matériau MTL avant conversion
+struct MtlEntry {
+    kd: Color,
+    ks: Color,
+    ke: Color,
+    ns: f64,
+    ni: f64,
+    dissolve: f64,
+    illum: u32,
+    map_kd: Option<Arc<dyn Texture>>,
+}
+
+impl MtlEntry {
+    fn to_material(&self) -> Arc<dyn Material> {
+        if !self.ke.near_zero() {
+            Arc::new(DiffuseLight::new(self.ke))
+        } else if matches!(self.illum, 4 | 6 | 7) || self.dissolve < 1.0 {
+            Arc::new(Dielectric::new(self.ni))
+        } else if matches!(self.illum, 3 | 5) {
+            // Exposant spéculaire élevé = reflet net
+            Arc::new(Metal::new(self.ks, f64::sqrt(2.0 / (self.ns + 2.0))))
+        } else if let Some(texture) = &self.map_kd {
+            Arc::new(Lambertian::textured(texture.clone()))
+        } else {
+            Arc::new(Lambertian::new(self.kd))
+        }
+    }
+}
+
+pub fn load_mtl(path: &Path) -> Result<HashMap<String, Arc<dyn Material>>, ObjError> {
+    let source = read(path)?;
+    parse_mtl(path, &source)
+}
+
+fn parse_mtl(path: &Path, source: &str) -> Result<HashMap<String, Arc<dyn Material>>, ObjError> {
+    let mut entries: Vec<(String, MtlEntry)> = Vec::new();
+
+    for (index, text) in source.lines().enumerate() {
+        let line = Line { file: path, number: index + 1 };
+        let text = text.split('#').next().unwrap_or("");
+        let mut words = text.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args: Vec<&str> = words.collect();
+
+        if keyword == "newmtl" {
+            let name = args.join(" ");
+            if name.is_empty() {
+                return Err(line.error("'newmtl' expects a name".to_string()));
+            }
+            entries.push((
+                name,
+                MtlEntry {
+                    kd: Color::new(0.8, 0.8, 0.8),
+                    ks: Color::new(0.0, 0.0, 0.0),
+                    ke: Color::new(0.0, 0.0, 0.0),
+                    ns: 0.0,
+                    ni: 1.5,
+                    dissolve: 1.0,
+                    illum: 2,
+                    map_kd: None,
+                },
+            ));
+            continue;
+        }
+
+        let Some((_, entry)) = entries.last_mut() else {
+            return Err(line.error(format!("'{}' before any 'newmtl'", keyword)));
+        };
+        match keyword {
+            "Kd" | "Ks" | "Ke" => {
+                let [r, g, b] = line.numbers::<3>(keyword, &args)?;
+                let color = Color::new(r, g, b);
+                match keyword {
+                    "Kd" => entry.kd = color,
+                    "Ks" => entry.ks = color,
+                    _ => entry.ke = color,
+                }
+            }
+            "Ns" => entry.ns = line.numbers::<1>(keyword, &args)?[0],
+            "Ni" => {
+                entry.ni = line.numbers::<1>(keyword, &args)?[0];
+                if !entry.ni.is_finite() || entry.ni <= 0.0 {
+                    return Err(line.error("'Ni' must be a positive index of refraction".to_string()));
+                }
+            }
+            "d" => entry.dissolve = line.numbers::<1>(keyword, &args)?[0],
+            "Tr" => entry.dissolve = 1.0 - line.numbers::<1>(keyword, &args)?[0],
+            "map_Kd" => {
+                // Les options (-s, -o, ...) ne sont pas gérées : le nom du fichier est le dernier mot
+                let file = args
+                    .last()
+                    .ok_or_else(|| line.error("'map_Kd' expects a file name".to_string()))?;
+                let file = path.parent().unwrap_or(Path::new("")).join(file);
+                let picture = image::read_image(&file)
+                    .map_err(|e| line.error(format!("{}: {}", file.display(), e)))?;
+                entry.map_kd = Some(Arc::new(ImageTexture::new(picture)));
+            }
+            "illum" => {
+                entry.illum = args
+                    .first()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| line.error("'illum' expects an integer".to_string()))?
+            }
+            _ => {}
+        }
+    }
+
+    Ok(entries
+        .into_iter()
+        .map(|(name, entry)| (name, entry.to_material()))
+        .collect())
+}
+
+// Contenu d'un fichier OBJ, avant la construction du maillage
+struct ObjData {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<[f64; 2]>,
+    faces: Vec<Face>,
+    materials: Vec<Arc<dyn Material>>,
+    groups: Vec<String>,
+}
+
+// `default_material` sert aux faces sans usemtl ; s'il est forcé, il remplace tous les matériaux du fichier
+pub fn load_obj(path: &Path, default_material: Arc<dyn Material>, force_material: bool) -> Result<ObjModel, ObjError> {
+    let source = read(path)?;
+    let data = parse_obj(path, &source, default_material, force_material)?;
+    let triangles = data.faces.len();
+    Ok(ObjModel {
+        mesh: Mesh::new(data.positions, data.normals, data.uvs, data.faces, data.materials),
+        groups: data.groups,
+        triangles,
+    })
+}
+
+// `path` situe les erreurs et les fichiers MTL
+fn parse_obj(
+    path: &Path,
+    source: &str,
+    default_material: Arc<dyn Material>,
+    force_material: bool,
+) -> Result<ObjData, ObjError> {
+    let dir = path.parent().unwrap_or(Path::new(""));
+
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<[f64; 2]> = Vec::new();
+    let mut faces: Vec<Face> = Vec::new();
+    let mut groups: Vec<String> = Vec::new();
+
+    let mut library: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let mut materials: Vec<Arc<dyn Material>> = vec![default_material];
+    let mut material_slots: HashMap<String, usize> = HashMap::new();
+    let mut current_material = 0;
+
+    for (index, text) in source.lines().enumerate() {
+        let line = Line { file: path, number: index + 1 };
+        let text = text.split('#').next().unwrap_or("");
+        let mut words = text.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = line.numbers::<3>(keyword, &args)?;
+                positions.push(Point3::new(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = line.numbers::<3>(keyword, &args)?;
+                normals.push(Vec3::new(x, y, z));
+            }
+            "vt" => {
+                // La 3e coordonnée (w) éventuelle est ignorée
+                let [u, v] = line.numbers::<2>(keyword, &args)?;
+                uvs.push([u, v]);
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(line.error("a face needs at least 3 vertices".to_string()));
+                }
+                // Chaque sommet : v, v/vt, v//vn ou v/vt/vn
+                let mut corners = Vec::with_capacity(args.len());
+                for corner in &args {
+                    let mut parts = corner.split('/');
+                    let p = line.index(parts.next().unwrap_or(""), positions.len(), "vertex")?;
+                    let t = match parts.next() {
+                        Some("") | None => None,
+                        Some(t) => Some(line.index(t, uvs.len(), "texture")?),
+                    };
+                    let n = match parts.next() {
+                        Some("") | None => None,
+                        Some(n) => Some(line.index(n, normals.len(), "normal")?),
+                    };
+                    if parts.next().is_some() {
+                        return Err(line.error(format!("invalid face vertex '{}'", corner)));
+                    }
+                    corners.push((p, t, n));
+                }
+                let all_uvs = corners.iter().all(|c| c.1.is_some());
+                let all_normals = corners.iter().all(|c| c.2.is_some());
+
+                // Découpage du polygone en éventail autour du premier sommet
+                for k in 1..corners.len() - 1 {
+                    let tri = [corners[0], corners[k], corners[k + 1]];
+                    faces.push(Face {
+                        positions: tri.map(|c| c.0),
+                        uvs: all_uvs.then(|| tri.map(|c| c.1.unwrap())),
+                        normals: all_normals.then(|| tri.map(|c| c.2.unwrap())),
+                        material: current_material,
+                    });
+                }
+            }
+            "g" | "o" => {
+                let name = args.join(" ");
+                if !name.is_empty() && !groups.contains(&name) {
+                    groups.push(name);
+                }
+            }
+            "mtllib" if !force_material => {
+                for file in &args {
+                    let mtl = load_mtl(&dir.join(file))?;
+                    library.extend(mtl);
+                }
+            }
+            "usemtl" if !force_material => {
+                let name = args.join(" ");
+                current_material = match material_slots.get(&name) {
+                    Some(&slot) => slot,
+                    None => {
+                        let material = library
+                            .get(&name)
+                            .cloned()
+                            .ok_or_else(|| line.error(format!("unknown material '{}'", name)))?;
+                        materials.push(material);
+                        material_slots.insert(name, materials.len() - 1);
+                        materials.len() - 1
+                    }
+                };
+            }
+            // Lissage, lignes, points, courbes... ne sont pas utilisés
+            _ => {}
+        }
+    }
+
+    if faces.is_empty() {
+        return Err(ObjError {
+            file: path.to_path_buf(),
+            line: 0,
+            message: "no face found".to_string(),
+        });
+    }
+
+    Ok(ObjData {
+        positions,
+        normals,
+        uvs,
+        faces,
+        materials,
+        groups,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ObjData, ObjError> {
+        let material = Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.8)));
+        parse_obj(Path::new("modele.obj"), source, material, false)
+    }
+
+    fn triangles(data: &ObjData) -> Vec<[usize; 3]> {
+        data.faces.iter().map(|f| f.positions).collect()
+    }
+
+    #[test]
+    fn negative_indices_count_from_the_last_vertex() {
+        let data = parse(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\n\
+             vn 0 0 1\n\
+             f -4/-3/-1 -3/-2/-1 -2/-1/-1\n\
+             v 5 5 5\n\
+             f -5 -4 -1\n",
+        )
+        .unwrap();
+        assert_eq!(triangles(&data), [[0, 1, 2], [0, 1, 4]]);
+        assert_eq!(data.faces[0].uvs, Some([0, 1, 2]));
+        assert_eq!(data.faces[0].normals, Some([0, 0, 0]));
+        assert_eq!(data.faces[1].uvs, None);
+    }
+
+    #[test]
+    fn polygons_are_split_in_a_fan() {
+        let data = parse(
+            "g dessus\n\
+             v 0 0 0\nv 1 0 0\nv 2 1 0\nv 1 2 0\nv 0 1 0\n\
+             vt 0 0\n\
+             f 1/1 2/1 3/1 4 5\n\
+             o dessus\n",
+        )
+        .unwrap();
+        assert_eq!(triangles(&data), [[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+        // Un sommet sans UV : aucun triangle du polygone n'en a
+        assert!(data.faces.iter().all(|f| f.uvs.is_none() && f.normals.is_none()));
+        assert_eq!(data.groups, ["dessus"]);
+    }
+
+    #[test]
+    fn errors_point_at_their_line() {
+        let cases = [
+            ("v 0 0 0\nv 1 0 0\n# commentaire\nf 1 2 3\n", 4, "vertex index 3 out of range (1..=2)"),
+            ("v 0 0 0\nv 1 0 x\n", 2, "invalid number 'x' in 'v'"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2\n", 4, "a face needs at least 3 vertices"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n", 4, "vertex index 0 out of range (1..=3)"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 -4\n", 4, "vertex index -4 out of range (1..=3)"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/1 2 3\n", 4, "texture index 1 out of range (1..=0)"),
+            ("v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvn 0 0 1\nf 1/1/1/1 2 3\n", 6, "invalid face vertex '1/1/1/1'"),
+            ("\n\nusemtl acier\n", 3, "unknown material 'acier'"),
+            ("v 0 0 0\n", 0, "no face found"),
+        ];
+        for (source, line, message) in cases {
+            let Err(error) = parse(source) else {
+                panic!("{:?} should fail", source);
+            };
+            assert_eq!((error.line, error.message.as_str()), (line, message), "{:?}", source);
+        }
+        let error = parse("v 0 0\n").err().unwrap();
+        assert_eq!(error.to_string(), "modele.obj:1: 'v' expects 3 numbers");
+    }
+
+    #[test]
+    fn mtl_rejects_a_non_positive_index_of_refraction() {
+        let path = Path::new("modele.mtl");
+        assert!(parse_mtl(path, "newmtl verre\nillum 7\nNi 1.33\n").unwrap().contains_key("verre"));
+        for ni in ["0", "-1.5", "nan", "inf"] {
+            let source = format!("newmtl verre\nillum 7\nNi {}\n", ni);
+            let error = parse_mtl(path, &source).err().unwrap();
+            assert_eq!(error.line, 3, "Ni {}", ni);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::common::{self, RenderRng, PI};
+use crate::vec3::Vec3;
+
+// Repère orthonormé construit autour d'une normale (w)
+#[derive(Copy, Clone)]
+pub struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    pub fn new(normal: Vec3) -> Onb {
+        let w = Vec3::unit_vector(normal);
+        let helper = if w.x().abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let v = Vec3::unit_vector(Vec3::cross(w, helper));
+        Onb { u: Vec3::cross(w, v), v, w }
+    }
+
+    // Coordonnées locales (a, b, c) vers le monde
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+}
+
+/*
+Densité de probabilité (par angle solide) des directions tirées par un matériau.
+value donne la densité d'une direction quelconque : c'est ce qui permet de comparer,
+pour une même direction, la stratégie du matériau et celle des lampes (MIS).
+*/
+#[derive(Copy, Clone)]
+pub enum Pdf {
+    // Directions réparties selon le cosinus autour de la normale (surface lambertienne)
+    Cosine(Onb),
+    // Toutes les directions de la sphère ont la même densité (milieu isotrope : fumée, brouillard)
+    Uniform,
+}
+
+impl Pdf {
+    pub fn cosine(normal: Vec3) -> Pdf {
+        Pdf::Cosine(Onb::new(normal))
+    }
+
+    pub fn value(&self, direction: Vec3) -> f64 {
+        match self {
+            Pdf::Cosine(uvw) => {
+                let cosine = Vec3::dot(Vec3::unit_vector(direction), uvw.w);
+                (cosine / PI).max(0.0)
+            }
+            Pdf::Uniform => 1.0 / (4.0 * PI),
+        }
+    }
+
+    pub fn generate(&self, rng: &mut RenderRng) -> Vec3 {
+        match self {
+            Pdf::Cosine(uvw) => uvw.local(random_cosine_direction(rng)),
+            Pdf::Uniform => random_sphere_direction(rng),
+        }
+    }
+}
+
+// Point uniforme du disque unité projeté sur l'hémisphère : densité cos / PI
+fn random_cosine_direction(rng: &mut RenderRng) -> Vec3 {
+    let r1 = common::random_double(rng);
+    let r2 = common::random_double(rng);
+    let phi = 2.0 * PI * r1;
+    let (x, y) = (phi.cos() * r2.sqrt(), phi.sin() * r2.sqrt());
+    Vec3::new(x, y, f64::sqrt(1.0 - r2))
+}
+
+// Direction uniforme sur la sphère : hauteur z uniforme dans [-1, 1] (Archimède), angle uniforme
+fn random_sphere_direction(rng: &mut RenderRng) -> Vec3 {
+    let z = 1.0 - 2.0 * common::random_double(rng);
+    let phi = 2.0 * PI * common::random_double(rng);
+    let r = f64::sqrt(1.0 - z * z);
+    Vec3::new(r * phi.cos(), r * phi.sin(), z)
+}
+
+// Poids MIS de la stratégie `f` face à la stratégie `g` (heuristique de puissance, exposant 2)
+pub fn power_heuristic(f: f64, g: f64) -> f64 {
+    let (f2, g2) = (f * f, g * g);
+    if f2 + g2 == 0.0 {
+        0.0
+    } else {
+        f2 / (f2 + g2)
+    }
+}
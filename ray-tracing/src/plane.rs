@@ -1,5 +1,6 @@
-use std::rc::Rc;
+use std::sync::Arc;
 use crate::vec3::{Point3, Vec3};
+use crate::aabb::Aabb;
 use crate::ray::Ray;
 use crate::material::Material;
 use crate::hittable::{HitRecord, Hittable};
@@ -7,14 +8,22 @@ use crate::hittable::{HitRecord, Hittable};
 pub struct Plane {
     point: Point3,  // Un point sur le plan
     normal: Vec3,   // La normale du plan
-    mat: Rc<dyn Material>,
+    tangent: Vec3,  // Axes u et v du plan, pour les textures
+    bitangent: Vec3,
+    mat: Arc<dyn Material>,
 }
 
 impl Plane {
-    pub fn new(point: Point3, normal: Vec3, mat: Rc<dyn Material>) -> Plane {
+    pub fn new(point: Point3, normal: Vec3, mat: Arc<dyn Material>) -> Plane {
+        let normal = Vec3::unit_vector(normal);  // On normalise la normale pour s'assurer qu'elle est de longueur 1
+        // N'importe quel vecteur non parallèle à la normale permet de construire les axes du plan
+        let helper = if normal.x().abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let bitangent = Vec3::unit_vector(Vec3::cross(normal, helper));
         Plane {
             point,
-            normal: Vec3::unit_vector(normal),  // On normalise la normale pour s'assurer qu'elle est de longueur 1
+            normal,
+            tangent: Vec3::cross(bitangent, normal),
+            bitangent,
             mat,
         }
     }
@@ -32,10 +41,19 @@ impl Hittable for Plane {
                 rec.t = t;
                 rec.p = r.at(t);
                 rec.set_face_normal(r, self.normal);
+                // Coordonnées en unités de la scène : une texture image se répète tous les 1.0
+                let offset = rec.p - self.point;
+                rec.u = Vec3::dot(offset, self.tangent);
+                rec.v = Vec3::dot(offset, self.bitangent);
                 rec.mat = Some(self.mat.clone());
                 return true;
             }
         }
         false
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Un plan est infini
+        None
+    }
 }
\ No newline at end of file
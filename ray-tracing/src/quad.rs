@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::common::{self, RenderRng};
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::triangle;
+use crate::vec3::{Point3, Vec3};
+
+/*
+Parallélogramme défini par un coin et ses deux côtés u et v : corner, corner + u, corner + v, corner + u + v.
+Idéal pour les murs et les plafonniers d'une boîte de Cornell.
+*/
+pub struct Quad {
+    corner: Point3,
+    u: Vec3,
+    v: Vec3,
+    normal: Vec3,
+    d: f64,   // équation du plan : dot(normal, p) = d
+    w: Vec3,  // sert à retrouver les coordonnées (alpha, beta) d'un point du plan
+    area: f64,
+    mat: Arc<dyn Material>,
+}
+
+impl Quad {
+    pub fn new(corner: Point3, u: Vec3, v: Vec3, mat: Arc<dyn Material>) -> Quad {
+        let n = Vec3::cross(u, v);
+        let normal = Vec3::unit_vector(n);
+        Quad {
+            corner,
+            u,
+            v,
+            normal,
+            d: Vec3::dot(normal, corner),
+            w: n / Vec3::dot(n, n),
+            area: n.vec_length(),
+            mat,
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let denom = Vec3::dot(self.normal, r.direction());
+        // Rayon parallèle au plan
+        if denom.abs() < 1e-8 {
+            return false;
+        }
+        let t = (self.d - Vec3::dot(self.normal, r.origin())) / denom;
+        if t <= t_min || t_max <= t {
+            return false;
+        }
+
+        // Coordonnées du point dans la base (u, v) : il faut alpha et beta dans [0, 1]
+        let p = r.at(t);
+        let planar = p - self.corner;
+        let alpha = Vec3::dot(self.w, Vec3::cross(planar, self.v));
+        let beta = Vec3::dot(self.w, Vec3::cross(self.u, planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = p;
+        rec.u = alpha;
+        rec.v = beta;
+        rec.set_face_normal(r, self.normal);
+        rec.mat = Some(self.mat.clone());
+        true
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let diagonal1 = Aabb::new(self.corner, self.corner + self.u + self.v);
+        let diagonal2 = Aabb::new(self.corner + self.u, self.corner + self.v);
+        // Un quad aligné sur les axes a une boîte plate : pad lui donne une épaisseur
+        Some(Aabb::surrounding_box(diagonal1, diagonal2).pad())
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY, &mut rec) {
+            return 0.0;
+        }
+        triangle::surface_pdf(direction, rec.t, self.normal, self.area)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut RenderRng) -> Vec3 {
+        let p = self.corner + common::random_double(rng) * self.u + common::random_double(rng) * self.v;
+        p - origin
+    }
+}
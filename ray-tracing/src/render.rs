@@ -0,0 +1,398 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::background::Background;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::common::{self, RenderRng};
+use crate::hittable::{HitRecord, Hittable};
+use crate::hittable_list::HittableList;
+use crate::light::Light;
+use crate::material::ScatterRecord;
+use crate::medium::Fog;
+use crate::pdf::{self, Pdf};
+use crate::ray::Ray;
+use crate::vec3::Vec3;
+
+// Taille (en pixels) d'une tuile carrée distribuée à un thread
+const TILE_SIZE: usize = 32;
+
+// Image en mémoire : les lignes sont stockées de haut en bas, chaque pixel contient
+// la moyenne linéaire de ses échantillons (la correction gamma se fait à l'écriture)
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![Color::default(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Color {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        self.pixels[y * self.width + x] = color;
+    }
+}
+
+// Rectangle de pixels [x0, x1[ x [y0, y1[ (coordonnées du framebuffer, y vers le bas)
+struct Tile {
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+}
+
+fn tiles(width: usize, height: usize) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for y0 in (0..height).step_by(TILE_SIZE) {
+        for x0 in (0..width).step_by(TILE_SIZE) {
+            tiles.push(Tile {
+                x0,
+                y0,
+                x1: (x0 + TILE_SIZE).min(width),
+                y1: (y0 + TILE_SIZE).min(height),
+            });
+        }
+    }
+    tiles
+}
+
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub threads: usize,
+    pub seed: u64,
+    pub background: Background,
+    pub lights: Vec<Light>,
+    pub area_lights: HittableList, // objets émissifs que l'on sait viser (sphères, quads, triangles)
+    pub fog: Option<Fog>,
+}
+
+/*
+Le rendu découpe l'image en tuiles. Chaque thread prend la prochaine tuile libre
+(compteur atomique), la calcule dans un buffer local puis la recopie dans le framebuffer partagé.
+Le monde et la caméra sont seulement lus, d'où l'exigence Send + Sync sur Hittable et Material.
+Chaque pixel tire ses nombres aléatoires de son propre générateur (graine globale + position) :
+une même graine donne la même image, quel que soit le nombre de threads.
+
+Sur une surface diffuse, la lumière des lampes surfaciques arrive par deux chemins :
+un rayon tiré vers une lampe (next event estimation) et le rebond tiré selon le matériau.
+Chacun est pondéré par l'heuristique de puissance (MIS) : la lampe est comptée une seule fois,
+par la stratégie la plus fiable pour cette direction (petite lampe : la première, grande lampe
+vue sur une surface brillante : la seconde).
+Un point de brouillard ou de fumée est traité comme une surface diffuse, avec la fonction de phase
+isotrope à la place du matériau : il est éclairé par les lampes de la même façon.
+*/
+pub fn render(world: &dyn Hittable, cam: &Camera, settings: &RenderSettings) -> Framebuffer {
+    let width = settings.width;
+    let height = settings.height;
+    let tiles = tiles(width, height);
+    let next_tile = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let framebuffer = Mutex::new(Framebuffer::new(width, height));
+
+    thread::scope(|scope| {
+        for _ in 0..settings.threads.max(1) {
+            scope.spawn(|| loop {
+                let index = next_tile.fetch_add(1, Ordering::Relaxed);
+                let Some(tile) = tiles.get(index) else {
+                    break;
+                };
+
+                let mut local = Vec::with_capacity((tile.x1 - tile.x0) * (tile.y1 - tile.y0));
+                for y in tile.y0..tile.y1 {
+                    // La caméra attend v = 0 en bas de l'image
+                    let j = height - 1 - y;
+                    for i in tile.x0..tile.x1 {
+                        let mut rng = common::pixel_rng(settings.seed, i, y);
+                        let mut pixel_color = Color::new(0.0, 0.0, 0.0);
+                        for _ in 0..settings.samples_per_pixel {
+                            let u = (i as f64 + common::random_double(&mut rng)) / (width - 1) as f64;
+                            let v = (j as f64 + common::random_double(&mut rng)) / (height - 1) as f64;
+                            let r = cam.get_ray(u, v, &mut rng);
+                            pixel_color = pixel_color
+                                + ray_color(&r, world, settings, settings.max_depth, None, &mut rng);
+                        }
+                        local.push(pixel_color / settings.samples_per_pixel as f64);
+                    }
+                }
+
+                let mut fb = framebuffer.lock().unwrap();
+                let mut pixels = local.into_iter();
+                for y in tile.y0..tile.y1 {
+                    for x in tile.x0..tile.x1 {
+                        fb.set(x, y, pixels.next().unwrap());
+                    }
+                }
+                drop(fb);
+
+                let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+                eprint!("\rTiles remaining: {} ", tiles.len() - finished);
+            });
+        }
+    });
+    eprint!("\nDone.\n");
+
+    framebuffer.into_inner().unwrap()
+}
+
+// Part de la lumière qui traverse le brouillard éventuel sur `distance`
+fn fog_transmittance(fog: Option<&Fog>, distance: f64) -> f64 {
+    fog.map_or(1.0, |fog| fog.transmittance(distance))
+}
+
+// Éclairage direct des lampes analytiques : une lampe ne compte que si rien ne la cache
+fn direct_light(
+    r: &Ray,
+    rec: &HitRecord,
+    world: &dyn Hittable,
+    lights: &[Light],
+    fog: Option<&Fog>,
+    rng: &mut RenderRng,
+) -> Color {
+    let mat = rec.mat.as_ref().unwrap();
+    let mut total = Color::new(0.0, 0.0, 0.0);
+    for light in lights {
+        let sample = light.sample(rec.p);
+        let contribution = mat.eval(r, rec, sample.direction);
+        if contribution.near_zero() || sample.intensity.near_zero() {
+            continue;
+        }
+        // Rayon d'ombre : une surface entre le point et la lampe la masque, un milieu l'atténue
+        let shadow = Ray::new(rec.p, sample.direction, r.time());
+        let transmittance = world.transmittance(&shadow, 0.001, sample.distance, rng);
+        if transmittance > 0.0 {
+            let transmittance = transmittance * fog_transmittance(fog, sample.distance);
+            total = total + transmittance * contribution * sample.intensity;
+        }
+    }
+    total
+}
+
+// Un rayon tiré vers une lampe surfacique au hasard, pondéré par MIS face à la densité du matériau
+fn sample_area_lights(
+    r: &Ray,
+    rec: &HitRecord,
+    world: &dyn Hittable,
+    lights: &HittableList,
+    fog: Option<&Fog>,
+    bsdf: &Pdf,
+    rng: &mut RenderRng,
+) -> Color {
+    let black = Color::new(0.0, 0.0, 0.0);
+    if lights.is_empty() {
+        return black;
+    }
+    let direction = lights.random(rec.p, rng);
+    let light_pdf = lights.pdf_value(rec.p, direction);
+    if light_pdf <= 0.0 {
+        return black;
+    }
+    let f = rec.mat.as_ref().unwrap().eval(r, rec, Vec3::unit_vector(direction));
+    if f.near_zero() {
+        return black;
+    }
+
+    // La lampe visée, puis ce qui se trouve avant elle : la lampe est exclue car touchée
+    // exactement en t_max (c'est le même objet que dans le monde)
+    let shadow = Ray::new(rec.p, direction, r.time());
+    let mut light_rec = HitRecord::new();
+    if !lights.hit(&shadow, 0.001, common::INFINITY, &mut light_rec) {
+        return black;
+    }
+    let mut transmittance = world.transmittance(&shadow, 0.001, light_rec.t, rng);
+    if transmittance <= 0.0 {
+        return black;
+    }
+    let emitted = light_rec.mat.as_ref().unwrap().emitted(&light_rec);
+    transmittance *= fog_transmittance(fog, light_rec.t * direction.vec_length());
+    let weight = pdf::power_heuristic(light_pdf, bsdf.value(direction));
+    weight * transmittance / light_pdf * f * emitted
+}
+
+// Un rayon tiré vers la carte d'environnement selon sa luminance, pondéré par MIS
+fn sample_background(
+    r: &Ray,
+    rec: &HitRecord,
+    world: &dyn Hittable,
+    background: &Background,
+    bsdf: &Pdf,
+    rng: &mut RenderRng,
+) -> Color {
+    let black = Color::new(0.0, 0.0, 0.0);
+    let Some((direction, env_pdf)) = background.sample(rng) else {
+        return black;
+    };
+    let f = rec.mat.as_ref().unwrap().eval(r, rec, Vec3::unit_vector(direction));
+    if f.near_zero() {
+        return black;
+    }
+    // Le ciel n'éclaire le point que si aucune surface ne le cache
+    let shadow = Ray::new(rec.p, direction, r.time());
+    let transmittance = world.transmittance(&shadow, 0.001, common::INFINITY, rng);
+    if transmittance <= 0.0 {
+        return black;
+    }
+    let weight = pdf::power_heuristic(env_pdf, bsdf.value(direction));
+    weight * transmittance / env_pdf * f * background.color(&shadow)
+}
+
+// bsdf_pdf : densité avec laquelle le matériau précédent a tiré ce rayon (None pour la caméra
+// et les rebonds spéculaires), pour pondérer la lumière d'une lampe touchée par hasard
+fn ray_color(
+    r: &Ray,
+    world: &dyn Hittable,
+    settings: &RenderSettings,
+    depth: i32,
+    bsdf_pdf: Option<f64>,
+    rng: &mut RenderRng,
+) -> Color {
+    // Si nous avons dépassé la profondeur maximale de rebonds, retour à la couleur noire
+    if depth <= 0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+
+    // Rien n'est touché : couleur de l'arrière-plan, qui a pu être visé depuis le point précédent
+    let mut rec = HitRecord::new();
+    if !world.hit_sampled(r, 0.001, common::INFINITY, &mut rec, rng) {
+        let color = settings.background.color(r);
+        return match bsdf_pdf {
+            Some(bsdf_pdf) => pdf::power_heuristic(bsdf_pdf, settings.background.pdf_value(r.direction())) * color,
+            None => color,
+        };
+    }
+
+    // Brouillard : avant d'atteindre la surface, le rayon peut être diffusé ou absorbé en chemin
+    if let Some(fog) = &settings.fog {
+        fog.scatter_before(r, &mut rec, rng);
+    }
+
+    // La surface touchée ajoute sa propre lumière à celle qu'elle renvoie
+    let mat = rec.mat.as_ref().unwrap();
+    let mut emitted = mat.emitted(&rec);
+    if let Some(bsdf_pdf) = bsdf_pdf {
+        if !emitted.near_zero() {
+            // Cette lampe a aussi pu être visée directement depuis le point précédent
+            let light_pdf = settings.area_lights.pdf_value(r.origin(), r.direction());
+            emitted = pdf::power_heuristic(bsdf_pdf, light_pdf) * emitted;
+        }
+    }
+
+    let mut srec = ScatterRecord::default();
+    if !mat.scatter(r, &rec, &mut srec, rng) {
+        return emitted;
+    }
+    let Some(bsdf) = srec.pdf else {
+        // Miroir, verre : un seul rayon possible, suivi tel quel
+        let bounce = ray_color(&srec.specular_ray, world, settings, depth - 1, None, rng);
+        return emitted + srec.attenuation * bounce;
+    };
+
+    // Les lampes analytiques n'ont pas de surface : le rebond ne peut pas les trouver,
+    // leur lumière n'est donc comptée qu'une fois, ici
+    let fog = settings.fog.as_ref();
+    let direct = direct_light(r, &rec, world, &settings.lights, fog, rng)
+        + sample_area_lights(r, &rec, world, &settings.area_lights, fog, &bsdf, rng)
+        + sample_background(r, &rec, world, &settings.background, &bsdf, rng);
+
+    // Rebond tiré selon le matériau
+    let direction = bsdf.generate(rng);
+    let pdf_value = bsdf.value(direction);
+    let f = mat.eval(r, &rec, Vec3::unit_vector(direction));
+    if pdf_value <= 0.0 || f.near_zero() {
+        return emitted + direct;
+    }
+    let scattered = Ray::new(rec.p, direction, r.time());
+    let bounce = ray_color(&scattered, world, settings, depth - 1, Some(pdf_value), rng);
+    emitted + direct + f * bounce / pdf_value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::bvh::BvhTree;
+    use crate::scene::{self, Overrides};
+
+    // Petite scène qui passe par tous les tirages aléatoires : caméra à ouverture, verre,
+    // métal flou, lampe surfacique, lampe ponctuelle, fumée et brouillard
+    const SCENE: &str = "
+render   width=80 aspect=5/3 samples=4 depth=8
+camera   lookfrom=0,1,4 lookat=0,0.5,0 vfov=40 aperture=0.05
+background sky
+material sol    lambertian albedo=0.5,0.5,0.5
+material verre  dielectric ior=1.5
+material or     metal albedo=0.8,0.6,0.2 fuzz=0.3
+material lampe  diffuse_light emit=4,4,4
+material fumee  isotropic albedo=0.8,0.8,0.8
+plane    point=0,0,0 normal=0,1,0 material=sol
+sphere   center=-0.8,0.5,0 radius=0.5 material=verre
+sphere   center=0.8,0.5,0 radius=0.5 material=or
+quad     corner=-0.5,2,-0.5 u=1,0,0 v=0,0,1 material=lampe
+light    point position=1,3,1 intensity=5,5,5
+define   boite cube min=-0.3,0,0.6 max=0.3,0.6,1.2 material=fumee
+medium   boundary=boite density=2 material=fumee
+fog      absorption=0.01 scattering=0.05
+";
+
+    fn render_with(threads: usize) -> Framebuffer {
+        let scene = scene::parse(Path::new("test.scene"), SCENE, &Overrides::default()).unwrap();
+        let settings = RenderSettings {
+            width: scene.width,
+            height: scene.height,
+            samples_per_pixel: scene.samples_per_pixel,
+            max_depth: scene.max_depth,
+            threads,
+            seed: 42,
+            background: scene.background,
+            lights: scene.lights,
+            area_lights: scene.area_lights,
+            fog: scene.fog,
+        };
+        let world = BvhTree::new(scene.world);
+        render(&world, &scene.camera, &settings)
+    }
+
+    fn bits(fb: &Framebuffer) -> Vec<[u64; 3]> {
+        let mut bits = Vec::new();
+        for y in 0..fb.height() {
+            for x in 0..fb.width() {
+                let c = fb.get(x, y);
+                bits.push([c.x().to_bits(), c.y().to_bits(), c.z().to_bits()]);
+            }
+        }
+        bits
+    }
+
+    #[test]
+    fn same_seed_same_image_whatever_the_threads() {
+        // 80x48 : six tuiles, réparties différemment selon le nombre de threads
+        let single = render_with(1);
+        let reference = bits(&single);
+        assert!(reference.iter().flatten().any(|&b| f64::from_bits(b) > 0.0));
+        for threads in [2, 3, 8] {
+            assert!(bits(&render_with(threads)) == reference, "{} threads differ from 1 thread", threads);
+        }
+    }
+}
@@ -0,0 +1,1196 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::animation::Animation;
+use crate::background::Background;
+use crate::camera::{Camera, CameraPose, CameraRig, FisheyeMapping, Projection};
+use crate::color::Color;
+use crate::constants::*;
+use crate::csg::{Csg, CsgOp};
+use crate::cube::Cube;
+use crate::cylinder::Cylinder;
+use crate::envmap::EnvironmentMap;
+use crate::hittable::Hittable;
+use crate::hittable_list::HittableList;
+use crate::keyframe::{Interpolation, Track};
+use crate::image;
+use crate::light::Light;
+use crate::mesh::{Face, Mesh};
+use crate::obj;
+use crate::matrix::Mat4;
+use crate::material::{Dielectric, DiffuseLight, Isotropic, Lambertian, Material, Metal};
+use crate::medium::{ConstantMedium, Fog, GridMedium};
+use crate::moving_sphere::MovingSphere;
+use crate::plane::Plane;
+use crate::quad::Quad;
+use crate::sphere::Sphere;
+use crate::texture::{CheckerTexture, ImageTexture, NoiseKind, NoiseTexture, SolidColor, Texture};
+use crate::tonemap::{PostProcess, ToneMap};
+use crate::transform::{Placement, Transform};
+use crate::triangle::Triangle;
+use crate::vec3::{Point3, Vec3};
+use crate::voxel::VoxelGrid;
+
+/*
+Format de scène : une instruction par ligne, '#' commence un commentaire.
+    <type> [noms...] clé=valeur clé=valeur ...
+Les vecteurs s'écrivent x,y,z et les nombres acceptent une fraction (16/9).
+
+    render   width=1200 aspect=16/9 samples=100 depth=50
+             (développement : exposure=EV tonemap=clamp|reinhard|extended|aces|agx white=4)
+    background color=0,0,0                (ou : background sky / background gradient bottom=.. top=..)
+    background envmap file=ciel.hdr rotate=90 intensity=1.5   (image .hdr ou PFM équirectangulaire)
+    camera   lookfrom=0,1.6,2 lookat=0,0,-0.5 vup=0,1,0 vfov=80
+             (profondeur de champ : aperture=0.1 focus_dist=2.5, netteté par défaut sur lookat)
+             (flou de bougé : shutter=0,1, instants d'ouverture et de fermeture de l'obturateur)
+    camera orthographic lookfrom=.. lookat=.. height=4      (hauteur visible en unités de la scène)
+    camera fisheye lookfrom=.. lookat=.. fov=180 mapping=equisolid   (ou equidistant)
+    camera equirectangular lookfrom=.. lookat=..           (panorama 360°, ratio 2:1 conseillé)
+    texture  damier checker even=0.9,0.9,0.9 odd=0.2,0.3,0.1 size=0.5   (cubes 3D de côté size)
+    texture  bois image file=textures/bois.png          (PPM ou PNG, chemin relatif au fichier de scène)
+    texture  pierre marble scale=4 color=1,1,1          (ou noise / turbulence ; option seed=)
+    texture  rouge solid color=0.8,0.1,0.1
+    material sol lambertian albedo=0.05,0.05,0.05
+    material or metal albedo=0.8,0.6,0.2 fuzz=0.0
+    material verre dielectric ior=1.5
+    material lampe diffuse_light emit=4,4,4
+    material fumee isotropic albedo=0.8,0.8,0.8         (fonction de phase d'un milieu)
+    light    point position=0,3,0 intensity=10,10,10           (éclairement = intensité / distance²)
+    light    spot position=0,3,0 lookat=0,0,0 intensity=20,20,20 angle=30 falloff=5   (ou direction=)
+    light    directional direction=-1,-2,-1 intensity=2,2,2   (soleil : sens de propagation de la lumière)
+Les clés albedo, emit, even et odd acceptent une couleur x,y,z ou le nom d'une texture (albedo=damier).
+    sphere   center=0,1,-2.5 radius=1 material=sol
+    sphere   center=0,1,0 center1=0,2,0 radius=0.5 material=sol   (en mouvement de center à center1,
+             entre les instants 0 et 1, ou times=t0,t1)
+    plane    point=0,0,-1 normal=0,1,0 material=sol
+    cube     min=0,0,0 max=0.7,0.7,0.7 material=or
+    quad     corner=0,0,0 u=1,0,0 v=0,0,1 material=or          (parallélogramme : corner, corner+u, corner+v)
+    cylinder base=-2,-1,-1 height=2.5 radius=0.5 material=or     (vertical, posé sur base)
+    cylinder p0=0,0,0 p1=1,1,0 radius=0.1 caps=false material=or   (axe quelconque, tube ouvert)
+    cone     p0=0,0,0 p1=0,1,0 radius0=0.5 radius1=0.2 material=or  (tronc de cône, options : caps=)
+    triangle p0=0,0,0 p1=1,0,0 p2=0,1,0 material=or      (options : n0= n1= n2= uv0= uv1= uv2=)
+    mesh     positions=0,0,0;1,0,0;1,1,0;0,1,0 indices=0,1,2;0,2,3 material=or
+             (options : normals= et uvs= donnés par sommet, dans le même ordre que positions)
+    obj      file=models/lapin.obj                (chemin relatif au fichier de scène)
+             (option : material= remplace les matériaux du fichier MTL)
+    csg      op=difference a=boite b=boule        (union, intersection ou difference de deux solides définis)
+    medium   boundary=boite density=2 material=fumee   (fumée de densité constante dans un solide défini)
+    volume   file=volumes/nuage.txt min=-1,0,-1 max=1,1,1 density=8 material=fumee
+             (grille de densités étirée sur la boîte min/max ; fichier texte "nx ny nz valeurs...",
+              ou flottants 32 bits bruts avec dims=64,64,64)
+    fog      absorption=0.01 scattering=0.05      (brouillard dans toute la scène, coefficients par unité)
+
+Une sphère, un quad ou un triangle non transformé dont le matériau est diffuse_light devient une lampe
+surfacique : il est visé directement depuis chaque surface diffuse (ombres douces peu bruitées).
+Tout objet accepte un placement : scale=2 (ou x,y,z), rotate=rx,ry,rz (degrés, autour de X puis Y puis Z),
+rotate_axis=x,y,z angle=degrés, translate=x,y,z. L'échelle s'applique en premier, la translation en dernier.
+Un placement peut être animé par des clés, interpolées à l'instant de chaque rayon :
+    key      roue time=0 translate=-2,0.5,0
+    key      roue time=1 translate=2,0.5,0 rotate=0,0,-360     (clés d'une piste, instants croissants)
+    cylinder p0=0,0,-0.1 p1=0,0,0.1 radius=0.5 material=or animate=roue   (après scale/rotate/translate)
+    key      camera time=0 lookfrom=0,1,5 lookat=0,1,0 vfov=40 (piste réservée à la caméra ; une valeur
+             absente reprend celle de la clé précédente, ou de la ligne camera)
+Une clé accepte interpolation=linear (par défaut) ou catmull_rom, pour aller d'elle à la suivante.
+Rendue avec --frames, une animation donne une image par instant n / fps, en secondes :
+    animation frames=48 fps=24 shutter_angle=180   (obturateur ouvert la moitié de chaque image)
+Une géométrie nommée est définie une fois puis instanciée autant de fois que voulu :
+    define   gemme obj file=models/gem.obj
+    instance gemme translate=1,0,0 rotate=0,45,0 scale=0.5
+*/
+
+#[derive(Debug)]
+pub struct SceneError {
+    pub file: PathBuf,
+    pub line: usize, // 0 quand l'erreur ne concerne pas une ligne précise (lecture du fichier)
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if self.line > 0 {
+            write!(f, ":{}", self.line)?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, ": '{}'", key)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Error for SceneError {}
+
+pub struct Scene {
+    pub world: HittableList,
+    pub camera: Camera,
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: i32,
+    pub max_depth: i32,
+    pub background: Background,
+    pub lights: Vec<Light>,
+    pub area_lights: HittableList,
+    pub post: PostProcess,
+    pub fog: Option<Fog>,
+    pub animation: Option<Animation>,
+    pub models: Vec<LoadedModel>, // fichiers OBJ chargés, dans l'ordre de la scène
+}
+
+// Résumé d'un fichier OBJ chargé, affiché au lancement
+pub struct LoadedModel {
+    pub file: PathBuf,
+    pub triangles: usize,
+    pub groups: Vec<String>,
+}
+
+// Réglages imposés depuis la ligne de commande, prioritaires sur la ligne `render`
+#[derive(Default)]
+pub struct Overrides {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub aspect: Option<f64>,
+    pub samples: Option<i32>,
+    pub depth: Option<i32>,
+    pub exposure: Option<f64>,
+    pub tone_map: Option<ToneMap>,
+}
+
+// Une ligne du fichier découpée en mots
+struct Statement<'a> {
+    file: &'a Path,
+    line: usize,
+    kind: String,
+    names: Vec<String>,
+    args: Vec<(String, String)>,
+    used: Vec<bool>,
+}
+
+impl<'a> Statement<'a> {
+    fn parse(file: &'a Path, line: usize, text: &str) -> Result<Option<Statement<'a>>, SceneError> {
+        let text = match text.find('#') {
+            Some(i) => &text[..i],
+            None => text,
+        };
+        let mut words = text.split_whitespace();
+        let Some(kind) = words.next() else {
+            return Ok(None);
+        };
+
+        let mut stmt = Statement {
+            file,
+            line,
+            kind: kind.to_string(),
+            names: Vec::new(),
+            args: Vec::new(),
+            used: Vec::new(),
+        };
+        for word in words {
+            match word.split_once('=') {
+                Some((key, value)) => {
+                    if key.is_empty() || value.is_empty() {
+                        return Err(stmt.error(Some(word), "expected key=value"));
+                    }
+                    if stmt.args.iter().any(|(k, _)| k == key) {
+                        return Err(stmt.error(Some(key), "duplicate key"));
+                    }
+                    stmt.args.push((key.to_string(), value.to_string()));
+                    stmt.used.push(false);
+                }
+                None if stmt.args.is_empty() => stmt.names.push(word.to_string()),
+                None => return Err(stmt.error(Some(word), "expected key=value")),
+            }
+        }
+        Ok(Some(stmt))
+    }
+
+    fn error(&self, key: Option<&str>, message: &str) -> SceneError {
+        SceneError {
+            file: self.file.to_path_buf(),
+            line: self.line,
+            key: key.map(|k| k.to_string()),
+            message: message.to_string(),
+        }
+    }
+
+    fn raw(&mut self, key: &str) -> Option<String> {
+        let i = self.args.iter().position(|(k, _)| k == key)?;
+        self.used[i] = true;
+        Some(self.args[i].1.clone())
+    }
+
+    fn name(&self, index: usize, what: &str) -> Result<String, SceneError> {
+        self.names
+            .get(index)
+            .cloned()
+            .ok_or_else(|| self.error(None, &format!("missing {} for '{}'", what, self.kind)))
+    }
+
+    fn opt_f64(&mut self, key: &str) -> Result<Option<f64>, SceneError> {
+        match self.raw(key) {
+            Some(value) => parse_number(&value)
+                .map(Some)
+                .ok_or_else(|| self.error(Some(key), &format!("invalid number '{}'", value))),
+            None => Ok(None),
+        }
+    }
+
+    fn f64(&mut self, key: &str) -> Result<f64, SceneError> {
+        self.opt_f64(key)?.ok_or_else(|| self.error(Some(key), "missing key"))
+    }
+
+    fn f64_or(&mut self, key: &str, default: f64) -> Result<f64, SceneError> {
+        Ok(self.opt_f64(key)?.unwrap_or(default))
+    }
+
+    fn usize_or(&mut self, key: &str, default: usize) -> Result<usize, SceneError> {
+        match self.raw(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| self.error(Some(key), &format!("invalid integer '{}'", value))),
+            None => Ok(default),
+        }
+    }
+
+    // Compte d'au moins 1 (échantillons, rebonds)
+    fn count_or(&mut self, key: &str, default: i32) -> Result<i32, SceneError> {
+        match self.raw(key) {
+            Some(value) => match value.parse::<i32>() {
+                Ok(n) if n >= 1 => Ok(n),
+                _ => Err(self.error(Some(key), &format!("invalid value '{}', expected an integer of at least 1", value))),
+            },
+            None => Ok(default),
+        }
+    }
+
+    fn bool_or(&mut self, key: &str, default: bool) -> Result<bool, SceneError> {
+        match self.raw(key).as_deref() {
+            Some("true") => Ok(true),
+            Some("false") => Ok(false),
+            Some(other) => Err(self.error(Some(key), &format!("invalid value '{}', expected true or false", other))),
+            None => Ok(default),
+        }
+    }
+
+    fn opt_vec3(&mut self, key: &str) -> Result<Option<Vec3>, SceneError> {
+        let Some(value) = self.raw(key) else {
+            return Ok(None);
+        };
+        let parts: Vec<Option<f64>> = value.split(',').map(parse_number).collect();
+        match parts[..] {
+            [Some(x), Some(y), Some(z)] => Ok(Some(Vec3::new(x, y, z))),
+            _ => Err(self.error(Some(key), &format!("invalid vector '{}', expected x,y,z", value))),
+        }
+    }
+
+    fn vec3(&mut self, key: &str) -> Result<Vec3, SceneError> {
+        self.opt_vec3(key)?.ok_or_else(|| self.error(Some(key), "missing key"))
+    }
+
+    fn vec3_or(&mut self, key: &str, default: Vec3) -> Result<Vec3, SceneError> {
+        Ok(self.opt_vec3(key)?.unwrap_or(default))
+    }
+
+    fn opt_uv(&mut self, key: &str) -> Result<Option<[f64; 2]>, SceneError> {
+        let Some(value) = self.raw(key) else {
+            return Ok(None);
+        };
+        let parts: Vec<Option<f64>> = value.split(',').map(parse_number).collect();
+        match parts[..] {
+            [Some(u), Some(v)] => Ok(Some([u, v])),
+            _ => Err(self.error(Some(key), &format!("invalid coordinates '{}', expected u,v", value))),
+        }
+    }
+
+    // Intervalle a,b avec a <= b (instants d'ouverture et de fermeture, début et fin d'un mouvement)
+    fn opt_range(&mut self, key: &str) -> Result<Option<[f64; 2]>, SceneError> {
+        let Some(value) = self.raw(key) else {
+            return Ok(None);
+        };
+        let parts: Vec<Option<f64>> = value.split(',').map(parse_number).collect();
+        match parts[..] {
+            [Some(a), Some(b)] if a <= b => Ok(Some([a, b])),
+            _ => Err(self.error(Some(key), &format!("invalid range '{}', expected start,end", value))),
+        }
+    }
+
+    // Liste de n-uplets séparés par ';', par exemple 0,0,0;1,0,0;0,1,0
+    fn opt_list(&mut self, key: &str, arity: usize) -> Result<Option<Vec<Vec<f64>>>, SceneError> {
+        let Some(value) = self.raw(key) else {
+            return Ok(None);
+        };
+        let mut list = Vec::new();
+        for item in value.split(';').filter(|item| !item.is_empty()) {
+            let parts: Option<Vec<f64>> = item.split(',').map(parse_number).collect();
+            match parts {
+                Some(parts) if parts.len() == arity => list.push(parts),
+                _ => {
+                    return Err(self.error(
+                        Some(key),
+                        &format!("invalid item '{}', expected {} comma-separated numbers", item, arity),
+                    ))
+                }
+            }
+        }
+        Ok(Some(list))
+    }
+
+    fn opt_scale(&mut self, key: &str) -> Result<Option<Vec3>, SceneError> {
+        // Un seul nombre pour une échelle uniforme, ou x,y,z
+        match self.args.iter().find(|(k, _)| k == key) {
+            Some((_, value)) if !value.contains(',') => {
+                Ok(self.opt_f64(key)?.map(|s| Vec3::new(s, s, s)))
+            }
+            _ => self.opt_vec3(key),
+        }
+    }
+
+    // Placement : échelle, puis rotations, puis translation ; None si aucune clé n'est donnée
+    fn placement(&mut self) -> Result<Option<Placement>, SceneError> {
+        let scale = self.opt_scale("scale")?;
+        let euler = self.opt_vec3("rotate")?;
+        let axis = self.opt_vec3("rotate_axis")?;
+        let angle = self.opt_f64("angle")?;
+        let translate = self.opt_vec3("translate")?;
+
+        let mut placement = Placement::default();
+        match (axis, angle) {
+            (Some(axis), Some(angle)) => {
+                if axis.near_zero() {
+                    return Err(self.error(Some("rotate_axis"), "axis must not be zero"));
+                }
+                (placement.axis, placement.angle) = (axis, angle);
+            }
+            (None, None) => {}
+            (Some(_), None) => return Err(self.error(Some("angle"), "missing key")),
+            (None, Some(_)) => return Err(self.error(Some("rotate_axis"), "missing key")),
+        }
+        if scale.is_none() && euler.is_none() && axis.is_none() && translate.is_none() {
+            return Ok(None);
+        }
+        placement.scale = scale.unwrap_or(placement.scale);
+        placement.rotate = euler.unwrap_or(placement.rotate);
+        placement.translate = translate.unwrap_or(placement.translate);
+        Ok(Some(placement))
+    }
+
+    // Passage d'une clé à la suivante, linéaire par défaut
+    fn interpolation(&mut self) -> Result<Interpolation, SceneError> {
+        match self.raw("interpolation") {
+            Some(name) => Interpolation::from_name(&name)
+                .ok_or_else(|| self.error(Some("interpolation"), "expected linear or catmull_rom")),
+            None => Ok(Interpolation::Linear),
+        }
+    }
+
+    // Matrice de placement : échelle, puis rotations, puis translation
+    fn transform(&mut self) -> Result<Option<Mat4>, SceneError> {
+        Ok(self.placement()?.map(|p| p.matrix()))
+    }
+
+    // Une couleur x,y,z ou le nom d'une texture déclarée plus haut
+    fn texture(&mut self, key: &str, textures: &HashMap<String, Arc<dyn Texture>>) -> Result<Arc<dyn Texture>, SceneError> {
+        let value = self.raw(key).ok_or_else(|| self.error(Some(key), "missing key"))?;
+        if value.contains(',') {
+            return Ok(Arc::new(SolidColor::new(self.vec3(key)?)));
+        }
+        textures
+            .get(&value)
+            .cloned()
+            .ok_or_else(|| self.error(Some(key), &format!("unknown texture '{}'", value)))
+    }
+
+    fn material(&mut self, materials: &HashMap<String, Arc<dyn Material>>) -> Result<Arc<dyn Material>, SceneError> {
+        let name = self.raw("material").ok_or_else(|| self.error(Some("material"), "missing key"))?;
+        materials
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| self.error(Some("material"), &format!("unknown material '{}'", name)))
+    }
+
+    // Toute clé non lue est une faute de frappe ou une clé non supportée
+    fn finish(&self) -> Result<(), SceneError> {
+        match self.used.iter().position(|used| !used) {
+            Some(i) => Err(self.error(
+                Some(&self.args[i].0),
+                &format!("unknown key for '{}'", self.kind),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+// Nombre ou fraction "a/b" ; nan, inf et les divisions par zéro sont refusés
+fn parse_number(text: &str) -> Option<f64> {
+    let value = match text.split_once('/') {
+        Some((num, den)) => num.trim().parse::<f64>().ok()? / den.trim().parse::<f64>().ok()?,
+        None => text.trim().parse().ok()?,
+    };
+    value.is_finite().then_some(value)
+}
+
+// Construit la géométrie décrite par une instruction (sphere, plane, cube, ...)
+fn object(
+    stmt: &mut Statement,
+    kind: &str,
+    path: &Path,
+    materials: &HashMap<String, Arc<dyn Material>>,
+    objects: &HashMap<String, Arc<dyn Hittable>>,
+    models: &mut Vec<LoadedModel>,
+) -> Result<Box<dyn Hittable>, SceneError> {
+    match kind {
+        "sphere" => {
+            let center = stmt.vec3("center")?;
+            let radius = stmt.f64("radius")?;
+            if radius <= 0.0 {
+                return Err(stmt.error(Some("radius"), "radius must be positive"));
+            }
+            let mat = stmt.material(materials)?;
+            match stmt.opt_vec3("center1")? {
+                Some(center1) => {
+                    let [time0, time1] = stmt.opt_range("times")?.unwrap_or([0.0, 1.0]);
+                    Ok(Box::new(MovingSphere::new(center, center1, time0, time1, radius, mat)))
+                }
+                None => Ok(Box::new(Sphere::new(center, radius, mat))),
+            }
+        }
+        "plane" => {
+            let point = stmt.vec3("point")?;
+            let normal = stmt.vec3("normal")?;
+            if normal.near_zero() {
+                return Err(stmt.error(Some("normal"), "normal must not be zero"));
+            }
+            Ok(Box::new(Plane::new(point, normal, stmt.material(materials)?)))
+        }
+        "quad" => {
+            let corner = stmt.vec3("corner")?;
+            let u = stmt.vec3("u")?;
+            let v = stmt.vec3("v")?;
+            if Vec3::cross(u, v).near_zero() {
+                return Err(stmt.error(Some("u"), "u and v must not be parallel"));
+            }
+            Ok(Box::new(Quad::new(corner, u, v, stmt.material(materials)?)))
+        }
+        "cube" => {
+            let min = stmt.vec3("min")?;
+            let max = stmt.vec3("max")?;
+            Ok(Box::new(Cube::new(min, max, stmt.material(materials)?)))
+        }
+        "cylinder" | "cone" => {
+            // Cylindre : p0= p1= radius=, ou l'ancienne forme verticale base= height= radius=
+            // Cône : p0= p1= radius0= radius1=
+            let (radius0, radius1) = if kind == "cylinder" {
+                let radius = stmt.f64("radius")?;
+                (radius, radius)
+            } else {
+                (stmt.f64("radius0")?, stmt.f64("radius1")?)
+            };
+            if radius0 < 0.0 || radius1 < 0.0 || radius0 + radius1 <= 0.0 {
+                return Err(stmt.error(Some("radius"), "radii must not be negative, nor both zero"));
+            }
+            let caps = stmt.bool_or("caps", true)?;
+            let mat = stmt.material(materials)?;
+
+            let base = if kind == "cylinder" { stmt.opt_vec3("base")? } else { None };
+            let shape = if let Some(base) = base {
+                let height = stmt.f64("height")?;
+                if height == 0.0 {
+                    return Err(stmt.error(Some("height"), "height must not be zero"));
+                }
+                Cylinder::new(base, height, radius0, mat)
+            } else {
+                let (p0, p1) = (stmt.vec3("p0")?, stmt.vec3("p1")?);
+                if (p1 - p0).near_zero() {
+                    return Err(stmt.error(Some("p1"), "both ends are at the same place"));
+                }
+                if kind == "cylinder" {
+                    Cylinder::between(p0, p1, radius0, mat)
+                } else {
+                    Cylinder::cone(p0, radius0, p1, radius1, mat)
+                }
+            };
+            Ok(Box::new(if caps { shape } else { shape.open() }))
+        }
+        "triangle" => {
+            let (p0, p1, p2) = (stmt.vec3("p0")?, stmt.vec3("p1")?, stmt.vec3("p2")?);
+            let mut triangle = Triangle::new(p0, p1, p2, stmt.material(materials)?);
+            match (stmt.opt_vec3("n0")?, stmt.opt_vec3("n1")?, stmt.opt_vec3("n2")?) {
+                (Some(n0), Some(n1), Some(n2)) => triangle = triangle.with_normals([n0, n1, n2]),
+                (None, None, None) => {}
+                _ => return Err(stmt.error(Some("n0"), "normals need n0, n1 and n2")),
+            }
+            match (stmt.opt_uv("uv0")?, stmt.opt_uv("uv1")?, stmt.opt_uv("uv2")?) {
+                (Some(uv0), Some(uv1), Some(uv2)) => triangle = triangle.with_uvs([uv0, uv1, uv2]),
+                (None, None, None) => {}
+                _ => return Err(stmt.error(Some("uv0"), "texture coordinates need uv0, uv1 and uv2")),
+            }
+            Ok(Box::new(triangle))
+        }
+        "mesh" => {
+            let positions: Vec<Point3> = stmt
+                .opt_list("positions", 3)?
+                .ok_or_else(|| stmt.error(Some("positions"), "missing key"))?
+                .iter()
+                .map(|p| Point3::new(p[0], p[1], p[2]))
+                .collect();
+            let count = positions.len();
+            let indices = stmt
+                .opt_list("indices", 3)?
+                .ok_or_else(|| stmt.error(Some("indices"), "missing key"))?;
+            let normals: Vec<Vec3> = stmt
+                .opt_list("normals", 3)?
+                .unwrap_or_default()
+                .iter()
+                .map(|n| Vec3::new(n[0], n[1], n[2]))
+                .collect();
+            let uvs: Vec<[f64; 2]> = stmt
+                .opt_list("uvs", 2)?
+                .unwrap_or_default()
+                .iter()
+                .map(|t| [t[0], t[1]])
+                .collect();
+            if !normals.is_empty() && normals.len() != count {
+                return Err(stmt.error(Some("normals"), "expected one normal per position"));
+            }
+            if !uvs.is_empty() && uvs.len() != count {
+                return Err(stmt.error(Some("uvs"), "expected one texture coordinate per position"));
+            }
+
+            let mut faces = Vec::with_capacity(indices.len());
+            for triple in &indices {
+                if triple.iter().any(|&i| i < 0.0 || i.fract() != 0.0 || i as usize >= count) {
+                    return Err(stmt.error(Some("indices"), &format!("invalid vertex index in {:?}", triple)));
+                }
+                let v = [triple[0] as usize, triple[1] as usize, triple[2] as usize];
+                faces.push(Face {
+                    positions: v,
+                    normals: (!normals.is_empty()).then_some(v),
+                    uvs: (!uvs.is_empty()).then_some(v),
+                    material: 0,
+                });
+            }
+            if faces.is_empty() {
+                return Err(stmt.error(Some("indices"), "mesh has no face"));
+            }
+            let material = stmt.material(materials)?;
+            Ok(Box::new(Mesh::new(positions, normals, uvs, faces, vec![material])))
+        }
+        "obj" => {
+            let file = stmt.raw("file").ok_or_else(|| stmt.error(Some("file"), "missing key"))?;
+            let file = path.parent().unwrap_or(Path::new("")).join(file);
+            let forced = stmt.args.iter().any(|(k, _)| k == "material");
+            let material = if forced {
+                stmt.material(materials)?
+            } else {
+                Arc::new(Lambertian::new(Color::new(0.8, 0.8, 0.8)))
+            };
+            let model = obj::load_obj(&file, material, forced)
+                .map_err(|e| stmt.error(Some("file"), &e.to_string()))?;
+            models.push(LoadedModel {
+                file,
+                triangles: model.triangles,
+                groups: model.groups,
+            });
+            Ok(Box::new(model.mesh))
+        }
+        "medium" => {
+            let name = stmt.raw("boundary").ok_or_else(|| stmt.error(Some("boundary"), "missing key"))?;
+            let boundary = objects
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| stmt.error(Some("boundary"), &format!("unknown object '{}', use 'define' first", name)))?;
+            let density = stmt.f64("density")?;
+            if density <= 0.0 {
+                return Err(stmt.error(Some("density"), "density must be positive"));
+            }
+            let medium = ConstantMedium::new(boundary, density, stmt.material(materials)?).ok_or_else(|| {
+                stmt.error(Some("boundary"), "boundary must be a closed solid (sphere, cube, capped cylinder or cone, csg)")
+            })?;
+            Ok(Box::new(medium))
+        }
+        "volume" => {
+            let file = stmt.raw("file").ok_or_else(|| stmt.error(Some("file"), "missing key"))?;
+            let file = path.parent().unwrap_or(Path::new("")).join(file);
+            let dims = match stmt.opt_vec3("dims")? {
+                Some(d) => {
+                    if [d.x(), d.y(), d.z()].iter().any(|&n| n < 1.0 || n.fract() != 0.0) {
+                        return Err(stmt.error(Some("dims"), "dimensions must be positive integers"));
+                    }
+                    Some([d.x() as usize, d.y() as usize, d.z() as usize])
+                }
+                None => None,
+            };
+            let (min, max) = (stmt.vec3("min")?, stmt.vec3("max")?);
+            if (0..3).any(|a| min[a] >= max[a]) {
+                return Err(stmt.error(Some("max"), "max must be greater than min on every axis"));
+            }
+            let density = stmt.f64_or("density", 1.0)?;
+            if density <= 0.0 {
+                return Err(stmt.error(Some("density"), "density must be positive"));
+            }
+            let grid = VoxelGrid::load(&file, dims).map_err(|e| stmt.error(Some("file"), &e.to_string()))?;
+            Ok(Box::new(GridMedium::new(grid, min, max, density, stmt.material(materials)?)))
+        }
+        "csg" => {
+            let name = stmt.raw("op").ok_or_else(|| stmt.error(Some("op"), "missing key"))?;
+            let op = CsgOp::from_name(&name)
+                .ok_or_else(|| stmt.error(Some("op"), "expected union, intersection or difference"))?;
+            let mut operand = |key: &str| {
+                let name = stmt.raw(key).ok_or_else(|| stmt.error(Some(key), "missing key"))?;
+                objects
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| stmt.error(Some(key), &format!("unknown object '{}', use 'define' first", name)))
+            };
+            let (a, b) = (operand("a")?, operand("b")?);
+            let csg = Csg::new(op, a, b).ok_or_else(|| {
+                stmt.error(Some("op"), "operands must be closed solids (sphere, cube, capped cylinder or cone, csg)")
+            })?;
+            Ok(Box::new(csg))
+        }
+        _ => Err(stmt.error(Some(kind), "unknown statement")),
+    }
+}
+
+// Clé qui donne le champ de vision de la projection
+fn fov_key(projection: &Projection) -> &'static str {
+    match projection {
+        Projection::Perspective { .. } => "vfov",
+        Projection::Orthographic { .. } => "height",
+        Projection::Fisheye { .. } | Projection::Equirectangular => "fov",
+    }
+}
+
+// Une pose dégénérée donnerait une base de la caméra nulle, donc des rayons NaN
+fn check_pose(stmt: &Statement, rig: &CameraRig, pose: &CameraPose) -> Result<(), SceneError> {
+    let fov = pose.fov;
+    let problem = match rig.projection {
+        Projection::Perspective { .. } if fov <= 0.0 || fov >= 180.0 => Some("field of view must be in ]0, 180["),
+        Projection::Orthographic { .. } if fov <= 0.0 => Some("visible height must be positive"),
+        Projection::Fisheye { .. } if fov <= 0.0 || fov > 360.0 => Some("field of view must be in ]0, 360]"),
+        _ => None,
+    };
+    if let Some(message) = problem {
+        return Err(stmt.error(Some(fov_key(&rig.projection)), message));
+    }
+    let view = pose.lookfrom - pose.lookat;
+    if view.near_zero() {
+        return Err(stmt.error(Some("lookat"), "lookat must differ from lookfrom"));
+    }
+    // Sinus de l'angle entre vup et la direction de visée (NaN si vup est nul)
+    let sin = Vec3::cross(rig.vup, view).vec_length() / (rig.vup.vec_length() * view.vec_length());
+    if sin.is_nan() || sin < 1e-6 {
+        return Err(stmt.error(Some("vup"), "vup must not be parallel to the view direction"));
+    }
+    Ok(())
+}
+
+// Applique les clés translate / rotate / scale éventuelles, puis l'animation
+fn transformed(
+    stmt: &mut Statement,
+    object: Box<dyn Hittable>,
+    tracks: &HashMap<String, Track<Placement>>,
+) -> Result<Box<dyn Hittable>, SceneError> {
+    let object: Box<dyn Hittable> = match stmt.transform()? {
+        Some(matrix) => match Transform::new(Arc::from(object), matrix) {
+            Some(t) => Box::new(t),
+            None => return Err(stmt.error(Some("scale"), "transform is not invertible")),
+        },
+        None => object,
+    };
+    animated(stmt, object, tracks)
+}
+
+// Clé animate=piste : le placement suit les clés de la piste au fil du temps
+fn animated(
+    stmt: &mut Statement,
+    object: Box<dyn Hittable>,
+    tracks: &HashMap<String, Track<Placement>>,
+) -> Result<Box<dyn Hittable>, SceneError> {
+    let Some(name) = stmt.raw("animate") else {
+        return Ok(object);
+    };
+    let track = tracks
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| stmt.error(Some("animate"), &format!("unknown track '{}', use 'key' first", name)))?;
+    match Transform::animated(Arc::from(object), track) {
+        Some(t) => Ok(Box::new(t)),
+        None => Err(stmt.error(Some("animate"), "a key of this track is not invertible")),
+    }
+}
+
+pub fn load(path: &Path, overrides: &Overrides) -> Result<Scene, SceneError> {
+    let source = fs::read_to_string(path).map_err(|e| SceneError {
+        file: path.to_path_buf(),
+        line: 0,
+        key: None,
+        message: e.to_string(),
+    })?;
+    parse(path, &source, overrides)
+}
+
+pub fn parse(path: &Path, source: &str, overrides: &Overrides) -> Result<Scene, SceneError> {
+    let mut world = HittableList::new();
+    let mut textures: HashMap<String, Arc<dyn Texture>> = HashMap::new();
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let mut objects: HashMap<String, Arc<dyn Hittable>> = HashMap::new();
+    let mut tracks: HashMap<String, Track<Placement>> = HashMap::new();
+    let mut camera_stmt = None;
+    let mut background = Background::Sky;
+    let mut lights = Vec::new();
+    let mut area_lights = HittableList::new();
+    let mut fog = None;
+    let mut camera_keys = Vec::new();
+    let mut animation = None;
+    let mut models = Vec::new();
+
+    // Valeurs par défaut : celles de constants.rs
+    let mut width = IMAGE_WIDTH as usize;
+    let mut height = None;
+    let mut aspect_ratio = ASPECT_RATIO;
+    let mut samples_per_pixel = SAMPLES_PER_PIXEL;
+    let mut max_depth = MAX_DEPTH;
+    let mut exposure = EXPOSURE;
+    let mut tone_map = ToneMap::Clamp;
+    let mut white = WHITE_POINT;
+
+    for (index, text) in source.lines().enumerate() {
+        let Some(mut stmt) = Statement::parse(path, index + 1, text)? else {
+            continue;
+        };
+
+        match stmt.kind.as_str() {
+            "render" => {
+                width = stmt.usize_or("width", width)?;
+                height = match stmt.usize_or("height", 0)? {
+                    0 => height,
+                    h => Some(h),
+                };
+                aspect_ratio = stmt.f64_or("aspect", aspect_ratio)?;
+                if !aspect_ratio.is_finite() || aspect_ratio <= 0.0 {
+                    return Err(stmt.error(Some("aspect"), "aspect ratio must be positive"));
+                }
+                samples_per_pixel = stmt.count_or("samples", samples_per_pixel)?;
+                max_depth = stmt.count_or("depth", max_depth)?;
+                exposure = stmt.f64_or("exposure", exposure)?;
+                if !exposure.is_finite() {
+                    return Err(stmt.error(Some("exposure"), "exposure must be a finite number of stops"));
+                }
+                white = stmt.f64_or("white", white)?;
+                if !white.is_finite() || white <= 0.0 {
+                    return Err(stmt.error(Some("white"), "white point must be positive"));
+                }
+                if let Some(name) = stmt.raw("tonemap") {
+                    tone_map = ToneMap::from_name(&name, white).ok_or_else(|| {
+                        stmt.error(Some("tonemap"), "expected clamp, reinhard, extended, aces or agx")
+                    })?;
+                }
+            }
+            "background" => {
+                let kind = stmt.names.first().cloned();
+                background = match kind.as_deref() {
+                    Some("sky") => Background::Sky,
+                    Some("gradient") => Background::Gradient {
+                        bottom: stmt.vec3("bottom")?,
+                        top: stmt.vec3("top")?,
+                    },
+                    Some("envmap") => {
+                        let file = stmt.raw("file").ok_or_else(|| stmt.error(Some("file"), "missing key"))?;
+                        let file = path.parent().unwrap_or(Path::new("")).join(file);
+                        let picture = image::read_hdr_image(&file)
+                            .map_err(|e| stmt.error(Some("file"), &format!("{}: {}", file.display(), e)))?;
+                        let rotation = stmt.f64_or("rotate", 0.0)?;
+                        let intensity = stmt.f64_or("intensity", 1.0)?;
+                        if intensity < 0.0 {
+                            return Err(stmt.error(Some("intensity"), "intensity must not be negative"));
+                        }
+                        Background::Environment(Arc::new(EnvironmentMap::new(picture, rotation, intensity)))
+                    }
+                    Some(other) => return Err(stmt.error(Some(other), "unknown background type")),
+                    None => Background::Solid(stmt.vec3("color")?),
+                };
+            }
+            "fog" => {
+                let absorption = stmt.f64_or("absorption", 0.0)?;
+                let scattering = stmt.f64_or("scattering", 0.0)?;
+                if absorption < 0.0 || scattering < 0.0 {
+                    return Err(stmt.error(Some("absorption"), "coefficients must not be negative"));
+                }
+                fog = Fog::new(absorption, scattering);
+            }
+            "camera" => {
+                if camera_stmt.is_some() {
+                    return Err(stmt.error(None, "camera declared twice"));
+                }
+                // Construite à la fin, une fois le ratio de l'image connu
+                camera_stmt = Some(stmt);
+                continue;
+            }
+            "texture" => {
+                let name = stmt.name(0, "texture name")?;
+                let kind = stmt.name(1, "texture type")?;
+                let texture: Arc<dyn Texture> = match kind.as_str() {
+                    "solid" => Arc::new(SolidColor::new(stmt.vec3("color")?)),
+                    "checker" => {
+                        let size = stmt.f64_or("size", 1.0)?;
+                        if size <= 0.0 {
+                            return Err(stmt.error(Some("size"), "checker size must be positive"));
+                        }
+                        let even = stmt.texture("even", &textures)?;
+                        let odd = stmt.texture("odd", &textures)?;
+                        Arc::new(CheckerTexture::new(size, even, odd))
+                    }
+                    "image" => {
+                        let file = stmt.raw("file").ok_or_else(|| stmt.error(Some("file"), "missing key"))?;
+                        let file = path.parent().unwrap_or(Path::new("")).join(file);
+                        let picture = image::read_image(&file)
+                            .map_err(|e| stmt.error(Some("file"), &format!("{}: {}", file.display(), e)))?;
+                        Arc::new(ImageTexture::new(picture))
+                    }
+                    "noise" | "turbulence" | "marble" => {
+                        let noise = match kind.as_str() {
+                            "noise" => NoiseKind::Noise,
+                            "turbulence" => NoiseKind::Turbulence,
+                            _ => NoiseKind::Marble,
+                        };
+                        let scale = stmt.f64_or("scale", 1.0)?;
+                        let color = stmt.vec3_or("color", Color::new(1.0, 1.0, 1.0))?;
+                        let seed = stmt.usize_or("seed", 0)? as u64;
+                        Arc::new(NoiseTexture::new(noise, scale, color, seed))
+                    }
+                    _ => return Err(stmt.error(Some(&kind), "unknown texture type")),
+                };
+                if textures.insert(name.clone(), texture).is_some() {
+                    return Err(stmt.error(Some(&name), "texture declared twice"));
+                }
+            }
+            "light" => {
+                let kind = stmt.name(0, "light type")?;
+                let intensity = stmt.vec3("intensity")?;
+                let light = match kind.as_str() {
+                    "point" => Light::Point {
+                        position: stmt.vec3("position")?,
+                        intensity,
+                    },
+                    "spot" => {
+                        let position = stmt.vec3("position")?;
+                        let direction = match (stmt.opt_vec3("direction")?, stmt.opt_vec3("lookat")?) {
+                            (Some(direction), None) => direction,
+                            (None, Some(lookat)) => lookat - position,
+                            _ => return Err(stmt.error(Some("direction"), "expected either direction or lookat")),
+                        };
+                        if direction.near_zero() {
+                            return Err(stmt.error(Some("direction"), "direction must not be zero"));
+                        }
+                        let angle = stmt.f64_or("angle", 30.0)?;
+                        if angle <= 0.0 || angle > 180.0 {
+                            return Err(stmt.error(Some("angle"), "angle must be in ]0, 180]"));
+                        }
+                        let falloff = stmt.f64_or("falloff", 5.0)?;
+                        Light::spot(position, direction, intensity, angle, falloff)
+                    }
+                    "directional" => {
+                        let direction = stmt.vec3("direction")?;
+                        if direction.near_zero() {
+                            return Err(stmt.error(Some("direction"), "direction must not be zero"));
+                        }
+                        Light::directional(direction, intensity)
+                    }
+                    _ => return Err(stmt.error(Some(&kind), "unknown light type")),
+                };
+                lights.push(light);
+            }
+            "material" => {
+                let name = stmt.name(0, "material name")?;
+                let kind = stmt.name(1, "material type")?;
+                let material: Arc<dyn Material> = match kind.as_str() {
+                    "lambertian" => Arc::new(Lambertian::textured(stmt.texture("albedo", &textures)?)),
+                    "metal" => Arc::new(Metal::textured(stmt.texture("albedo", &textures)?, stmt.f64_or("fuzz", 0.0)?)),
+                    "diffuse_light" => Arc::new(DiffuseLight::textured(stmt.texture("emit", &textures)?)),
+                    "isotropic" => Arc::new(Isotropic::textured(stmt.texture("albedo", &textures)?)),
+                    "dielectric" => {
+                        let ir = stmt.f64("ior")?;
+                        if ir <= 0.0 {
+                            return Err(stmt.error(Some("ior"), "index of refraction must be positive"));
+                        }
+                        Arc::new(Dielectric::new(ir))
+                    }
+                    _ => return Err(stmt.error(Some(&kind), "unknown material type")),
+                };
+                if materials.insert(name.clone(), material).is_some() {
+                    return Err(stmt.error(Some(&name), "material declared twice"));
+                }
+            }
+            "define" => {
+                // Géométrie nommée, partagée par toutes ses instances et absente du monde elle-même
+                let name = stmt.name(0, "object name")?;
+                let kind = stmt.name(1, "object type")?;
+                let object = object(&mut stmt, &kind, path, &materials, &objects, &mut models)?;
+                let object = transformed(&mut stmt, object, &tracks)?;
+                if objects.insert(name.clone(), Arc::from(object)).is_some() {
+                    return Err(stmt.error(Some(&name), "object defined twice"));
+                }
+            }
+            "instance" => {
+                let name = stmt.name(0, "object name")?;
+                let object = objects
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| stmt.error(Some(&name), "unknown object, use 'define' first"))?;
+                let matrix = stmt.transform()?.unwrap_or_else(Mat4::identity);
+                let instance = Transform::new(object, matrix)
+                    .ok_or_else(|| stmt.error(Some("scale"), "transform is not invertible"))?;
+                world.add(animated(&mut stmt, Box::new(instance), &tracks)?);
+            }
+            "key" => {
+                // Clé d'une piste d'animation : le placement de la piste à l'instant time=
+                let name = stmt.name(0, "track name")?;
+                if name == "camera" {
+                    // Lue à la fin, avec la ligne camera dont elle reprend les valeurs absentes
+                    camera_keys.push(stmt);
+                    continue;
+                }
+                let time = stmt.f64("time")?;
+                let placement = stmt.placement()?.unwrap_or_default();
+                let interpolation = stmt.interpolation()?;
+                if !tracks.entry(name).or_insert_with(Track::new).add(time, placement, interpolation) {
+                    return Err(stmt.error(Some("time"), "keys of a track must be given in increasing time"));
+                }
+            }
+            "animation" => {
+                if animation.is_some() {
+                    return Err(stmt.error(None, "animation declared twice"));
+                }
+                let frames = stmt.usize_or("frames", 0)?;
+                if frames == 0 {
+                    return Err(stmt.error(Some("frames"), "expected a positive number of frames"));
+                }
+                let fps = stmt.f64_or("fps", 24.0)?;
+                if fps <= 0.0 {
+                    return Err(stmt.error(Some("fps"), "frame rate must be positive"));
+                }
+                let angle = stmt.f64_or("shutter_angle", 180.0)?;
+                if !(0.0..=360.0).contains(&angle) {
+                    return Err(stmt.error(Some("shutter_angle"), "expected an angle between 0 and 360"));
+                }
+                animation = Some((frames, fps, angle / 360.0));
+            }
+            _ => {
+                let kind = stmt.kind.clone();
+                // Seules ces formes savent être visées, et seulement immobiles et sans placement
+                // (une échelle changerait la densité de tirage)
+                let is_area_light = matches!(kind.as_str(), "sphere" | "quad" | "triangle")
+                    && !stmt
+                        .args
+                        .iter()
+                        .any(|(k, _)| {
+                            matches!(k.as_str(), "scale" | "rotate" | "rotate_axis" | "translate" | "animate" | "center1")
+                        })
+                    && stmt
+                        .args
+                        .iter()
+                        .any(|(k, v)| k == "material" && materials.get(v).is_some_and(|m| m.is_light()));
+                let object = object(&mut stmt, &kind, path, &materials, &objects, &mut models)?;
+                let object = transformed(&mut stmt, object, &tracks)?;
+                if is_area_light {
+                    // Partagé entre le monde (pour être vu) et la liste des lampes (pour être visé)
+                    let shared: Arc<dyn Hittable> = Arc::from(object);
+                    area_lights.add(Box::new(shared.clone()));
+                    world.add(Box::new(shared));
+                } else {
+                    world.add(object);
+                }
+            }
+        }
+        stmt.finish()?;
+    }
+
+    // Changer la largeur ou le ratio en ligne de commande recalcule la hauteur
+    if overrides.width.is_some() || overrides.aspect.is_some() {
+        height = None;
+    }
+    let width = overrides.width.unwrap_or(width);
+    let aspect_ratio = overrides.aspect.unwrap_or(aspect_ratio);
+    let height = overrides.height.or(height).unwrap_or((width as f64 / aspect_ratio) as usize);
+    let samples_per_pixel = overrides.samples.unwrap_or(samples_per_pixel);
+    let max_depth = overrides.depth.unwrap_or(max_depth);
+    let exposure = overrides.exposure.unwrap_or(exposure);
+    // Le point blanc est celui de la scène, même si l'opérateur vient de la ligne de commande
+    let tone_map = match overrides.tone_map.unwrap_or(tone_map) {
+        ToneMap::ExtendedReinhard { .. } => ToneMap::ExtendedReinhard { white },
+        other => other,
+    };
+
+    // Un ratio extrême donnerait une hauteur nulle, ou un framebuffer impossible à allouer
+    if width < 2 || height < 2 || width > MAX_IMAGE_SIDE || height > MAX_IMAGE_SIDE {
+        return Err(SceneError {
+            file: path.to_path_buf(),
+            line: 0,
+            key: None,
+            message: format!(
+                "image size {}x{} is out of range, each side must be between 2 and {}",
+                width, height, MAX_IMAGE_SIDE
+            ),
+        });
+    }
+    let aspect_ratio = width as f64 / height as f64;
+
+    // Réglages fixes de la caméra, pose de la ligne `camera` et obturateur des images fixes
+    let (rig, pose, shutter) = match camera_stmt {
+        Some(mut stmt) => {
+            let lookfrom = stmt.vec3_or("lookfrom", Point3::new(0.0, 0.0, 0.0))?;
+            let lookat = stmt.vec3_or("lookat", Point3::new(0.0, 0.0, -1.0))?;
+            let aperture = stmt.f64_or("aperture", 0.0)?;
+            if aperture < 0.0 {
+                return Err(stmt.error(Some("aperture"), "aperture must not be negative"));
+            }
+            // Par défaut, la netteté est faite sur le point visé
+            let focus_dist = stmt.opt_f64("focus_dist")?;
+            if focus_dist.is_some_and(|d| d <= 0.0) {
+                return Err(stmt.error(Some("focus_dist"), "focus distance must be positive"));
+            }
+            let kind = stmt.names.first().cloned();
+            let projection = match kind.as_deref() {
+                None | Some("perspective") => Projection::Perspective {
+                    vfov: stmt.f64_or("vfov", 90.0)?,
+                },
+                Some("orthographic") => Projection::Orthographic {
+                    height: stmt.f64_or("height", 2.0)?,
+                },
+                Some("fisheye") => Projection::Fisheye {
+                    fov: stmt.f64_or("fov", 180.0)?,
+                    mapping: match stmt.raw("mapping").as_deref() {
+                        None | Some("equidistant") => FisheyeMapping::Equidistant,
+                        Some("equisolid") => FisheyeMapping::Equisolid,
+                        Some(_) => return Err(stmt.error(Some("mapping"), "expected equidistant or equisolid")),
+                    },
+                },
+                Some("equirectangular") => Projection::Equirectangular,
+                Some(other) => return Err(stmt.error(Some(other), "unknown projection")),
+            };
+            let rig = CameraRig {
+                vup: stmt.vec3_or("vup", Vec3::new(0.0, 1.0, 0.0))?,
+                projection,
+                aspect_ratio,
+                aperture,
+                focus_dist,
+            };
+            let shutter = stmt.opt_range("shutter")?.unwrap_or([0.0, 0.0]);
+            let pose = CameraPose { lookfrom, lookat, fov: rig.fov() };
+            check_pose(&stmt, &rig, &pose)?;
+            stmt.finish()?;
+            (rig, pose, shutter)
+        }
+        None => (
+            CameraRig {
+                vup: Vec3::new(0.0, 1.0, 0.0),
+                projection: Projection::Perspective { vfov: 90.0 },
+                aspect_ratio,
+                aperture: 0.0,
+                focus_dist: Some(1.0),
+            },
+            CameraPose {
+                lookfrom: Point3::new(0.0, 0.0, 0.0),
+                lookat: Point3::new(0.0, 0.0, -1.0),
+                fov: 90.0,
+            },
+            [0.0, 0.0],
+        ),
+    };
+
+    // Clés de la caméra : le champ se donne avec la clé de la projection (vfov, height ou fov)
+    let fov_key = fov_key(&rig.projection);
+    let mut camera_track: Option<Track<CameraPose>> = None;
+    let mut previous = pose;
+    for mut stmt in camera_keys {
+        let time = stmt.f64("time")?;
+        let key = CameraPose {
+            lookfrom: stmt.vec3_or("lookfrom", previous.lookfrom)?,
+            lookat: stmt.vec3_or("lookat", previous.lookat)?,
+            fov: stmt.f64_or(fov_key, previous.fov)?,
+        };
+        check_pose(&stmt, &rig, &key)?;
+        let interpolation = stmt.interpolation()?;
+        if !camera_track.get_or_insert_with(Track::new).add(time, key, interpolation) {
+            return Err(stmt.error(Some("time"), "keys of a track must be given in increasing time"));
+        }
+        stmt.finish()?;
+        previous = key;
+    }
+
+    // Image fixe : la caméra animée est prise à l'ouverture de l'obturateur
+    let [open, close] = shutter;
+    let still = camera_track.as_ref().map_or(pose, |track| track.at(open));
+    let camera = rig.camera(still).with_shutter(open, close);
+    let animation = animation.map(|(frames, fps, shutter)| Animation {
+        frames,
+        fps,
+        shutter,
+        rig,
+        pose,
+        camera_track,
+    });
+
+    Ok(Scene {
+        world,
+        camera,
+        width,
+        height,
+        samples_per_pixel,
+        max_depth,
+        background,
+        lights,
+        area_lights,
+        post: PostProcess { exposure, tone_map },
+        fog,
+        animation,
+        models,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> SceneError {
+        parse(Path::new("essai.scene"), source, &Overrides::default()).err().unwrap()
+    }
+
+    #[test]
+    fn render_rejects_non_finite_exposure_and_white() {
+        for (source, key) in [
+            ("render exposure=nan", "exposure"),
+            ("render exposure=-inf", "exposure"),
+            ("render white=nan", "white"),
+            ("render white=0", "white"),
+        ] {
+            let e = error(source);
+            assert_eq!((e.line, e.key.as_deref()), (1, Some(key)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn numbers_must_be_finite() {
+        assert_eq!(parse_number(" 1/4 "), Some(0.25));
+        assert_eq!(parse_number("-2e3"), Some(-2000.0));
+        for text in ["nan", "inf", "-infinity", "1/0", "0/0", "1e400", "1/2/3", ""] {
+            assert_eq!(parse_number(text), None, "{}", text);
+        }
+
+        let material = "material blanc lambertian albedo=0.73,0.73,0.73\n";
+        for (object, key) in [
+            ("sphere center=nan,0,0 radius=1 material=blanc", "center"),
+            ("sphere center=0,0,0 radius=inf material=blanc", "radius"),
+            ("quad corner=0,0,0 u=1/0,0,0 v=0,0,1 material=blanc", "u"),
+        ] {
+            let e = error(&format!("{}{}", material, object));
+            assert_eq!((e.line, e.key.as_deref()), (2, Some(key)), "{}", object);
+            assert!(e.message.starts_with("invalid "), "{}", e.message);
+        }
+    }
+}
@@ -1,55 +1,121 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
+use crate::aabb::Aabb;
+use crate::common::{self, RenderRng, PI};
+use crate::pdf::Onb;
 use crate::ray::Ray;
 use crate::material::Material;
 use crate::vec3::{Point3, Vec3};
-use crate::hittable::{HitRecord, Hittable};
+use crate::hittable::{self, HitRecord, Hittable, Interval};
 
 
 pub struct Sphere {
     center: Point3,
     radius: f64,
-    mat: Rc<dyn Material>
+    mat: Arc<dyn Material>
 }
  
 impl Sphere {
-    pub fn new(cen: Point3, r: f64, mat: Rc<dyn Material>) -> Sphere {
+    pub fn new(cen: Point3, r: f64, mat: Arc<dyn Material>) -> Sphere {
         Sphere {
             center: cen,
             radius: r,
             mat,
         }
     }
+
+    // p : point de la sphère unité centrée à l'origine.
+    // u : angle autour de l'axe Y depuis X = -1, v : angle depuis Y = -1 (pôle sud), ramenés à [0, 1]
+    fn get_uv(p: Point3) -> (f64, f64) {
+        let theta = f64::acos(-p.y());
+        let phi = f64::atan2(-p.z(), p.x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
 }
  
-impl Hittable for Sphere {
-    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        let oc = r.origin() - self.center;
-        let a = r.direction().length_squared();
-        let half_b = Vec3::dot(oc, r.direction());
-        let c = oc.length_squared() - self.radius * self.radius;
-        let discriminant = half_b * half_b - a * c;
-        if discriminant < 0.0 {
-            return false;
-        }
+// Intersection avec une sphère de centre et rayon donnés, partagée avec MovingSphere
+pub fn hit_sphere(
+    center: Point3,
+    radius: f64,
+    mat: &Arc<dyn Material>,
+    r: &Ray,
+    t_min: f64,
+    t_max: f64,
+    rec: &mut HitRecord,
+) -> bool {
+    let oc = r.origin() - center;
+    let a = r.direction().length_squared();
+    let half_b = Vec3::dot(oc, r.direction());
+    let c = oc.length_squared() - radius * radius;
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 {
+        return false;
+    }
  
-        // On va sélectionner grace a ce calcul, l'intersection la plus proche de la camera puisque le reste des intersetion sera en back face
-        let sqrt_d = f64::sqrt(discriminant);
-        let mut root = (-half_b - sqrt_d) / a;
+    // On va sélectionner grace a ce calcul, l'intersection la plus proche de la camera puisque le reste des intersetion sera en back face
+    let sqrt_d = f64::sqrt(discriminant);
+    let mut root = (-half_b - sqrt_d) / a;
+    if root <= t_min || t_max <= root {
+        root = (-half_b + sqrt_d) / a;
         if root <= t_min || t_max <= root {
-            root = (-half_b + sqrt_d) / a;
-            if root <= t_min || t_max <= root {
-                return false;
-            }
+            return false;
         }
+    }
  
-        rec.t = root;
-        rec.p = r.at(rec.t);
-        let outward_normal = (rec.p - self.center) / self.radius;
-        rec.set_face_normal(r, outward_normal);
-        rec.mat = Some(self.mat.clone());
+    rec.t = root;
+    rec.p = r.at(rec.t);
+    let outward_normal = (rec.p - center) / radius;
+    rec.set_face_normal(r, outward_normal);
+    (rec.u, rec.v) = Sphere::get_uv(outward_normal);
+    rec.mat = Some(mat.clone());
+    true
+}
+
+impl Hittable for Sphere {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        hit_sphere(self.center, self.radius, &self.mat, r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+
+    fn is_solid(&self) -> bool {
         true
     }
+
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        hittable::convex_interval(self, r)
+    }
+
+    // Vue depuis `origin`, la sphère occupe un cône : on tire uniformément dans ce cône
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let distance_squared = (self.center - origin).length_squared();
+        let mut rec = HitRecord::new();
+        if distance_squared <= self.radius * self.radius
+            || !self.hit(&Ray::new(origin, direction, 0.0), 0.001, f64::INFINITY, &mut rec)
+        {
+            return 0.0;
+        }
+        let cos_theta_max = f64::sqrt(1.0 - self.radius * self.radius / distance_squared);
+        1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+    }
+
+    fn random(&self, origin: Point3, rng: &mut RenderRng) -> Vec3 {
+        let direction = self.center - origin;
+        let distance_squared = direction.length_squared();
+        if distance_squared <= self.radius * self.radius {
+            // Depuis l'intérieur, pas de cône : la densité vaudra 0 et l'échantillon sera ignoré
+            return direction;
+        }
+        let r1 = common::random_double(rng);
+        let r2 = common::random_double(rng);
+        let z = 1.0 + r2 * (f64::sqrt(1.0 - self.radius * self.radius / distance_squared) - 1.0);
+        let phi = 2.0 * PI * r1;
+        let sin_theta = f64::sqrt(1.0 - z * z);
+        Onb::new(direction).local(Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z))
+    }
 }
 
 /*
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use crate::color::{self, Color};
+use crate::image::Picture;
+use crate::perlin::Perlin;
+use crate::vec3::Point3;
+
+/*
+Une texture donne la couleur d'une surface au point touché.
+Elle peut utiliser les coordonnées de surface (u, v) remplies par chaque primitive,
+ou directement la position p dans l'espace (texture "solide", indépendante de la forme).
+*/
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color;
+}
+
+pub struct SolidColor {
+    color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> Color {
+        self.color
+    }
+}
+
+// Damier en 3D : des cubes de côté `size` alternent entre les deux textures
+pub struct CheckerTexture {
+    size: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+impl CheckerTexture {
+    pub fn new(size: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> CheckerTexture {
+        CheckerTexture { size, even, odd }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> Color {
+        let cell = (p.x() / self.size).floor() + (p.y() / self.size).floor() + (p.z() / self.size).floor();
+        if cell.rem_euclid(2.0) == 0.0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+// Image plaquée avec les coordonnées (u, v), répétée au-delà de [0, 1]
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>, // couleurs linéaires, lignes de haut en bas
+}
+
+impl ImageTexture {
+    pub fn new(picture: Picture) -> ImageTexture {
+        ImageTexture {
+            width: picture.width,
+            height: picture.height,
+            pixels: picture.pixels.into_iter().map(color::to_linear).collect(),
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> Color {
+        // v = 0 en bas de l'image, alors que la première ligne stockée est celle du haut
+        let u = u.rem_euclid(1.0);
+        let v = 1.0 - v.rem_euclid(1.0);
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.pixels[j * self.width + i]
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NoiseKind {
+    Noise,      // bruit de Perlin lissé
+    Turbulence, // somme de plusieurs octaves, aspect nuageux
+    Marble,     // veines : sinus déphasé par la turbulence
+}
+
+pub struct NoiseTexture {
+    noise: Perlin,
+    kind: NoiseKind,
+    scale: f64,
+    color: Color,
+}
+
+impl NoiseTexture {
+    pub fn new(kind: NoiseKind, scale: f64, color: Color, seed: u64) -> NoiseTexture {
+        NoiseTexture {
+            noise: Perlin::new(seed),
+            kind,
+            scale,
+            color,
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> Color {
+        let scaled = self.scale * *p;
+        let intensity = match self.kind {
+            NoiseKind::Noise => 0.5 * (1.0 + self.noise.noise(&scaled)),
+            NoiseKind::Turbulence => self.noise.turbulence(&scaled, 7),
+            NoiseKind::Marble => 0.5 * (1.0 + f64::sin(scaled.z() + 10.0 * self.noise.turbulence(p, 7))),
+        };
+        intensity * self.color
+    }
+}
@@ -0,0 +1,125 @@
+use crate::color::Color;
+use crate::vec3::Vec3;
+
+/*
+Développement de l'image : le framebuffer contient une radiance linéaire non bornée,
+l'écran attend des valeurs dans [0, 1]. On applique l'exposition (en EV : +1 double la lumière),
+puis un opérateur de tone mapping qui ramène les hautes lumières dans [0, 1].
+L'encodage sRGB (OETF) est fait ensuite, à la conversion en octets (color::to_rgb8).
+*/
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ToneMap {
+    Clamp,                            // coupe au-dessus de 1 : fidèle aux anciens rendus
+    Reinhard,                         // L / (1 + L) sur la luminance
+    ExtendedReinhard { white: f64 },  // idem, mais la luminance `white` atteint exactement le blanc
+    Aces,                             // courbe filmique ACES (approximation de Stephen Hill)
+    Agx,                              // AgX : désature doucement les très hautes lumières
+}
+
+impl ToneMap {
+    pub fn from_name(name: &str, white: f64) -> Option<ToneMap> {
+        match name.to_ascii_lowercase().as_str() {
+            "clamp" => Some(ToneMap::Clamp),
+            "reinhard" => Some(ToneMap::Reinhard),
+            "extended" | "reinhard_extended" => Some(ToneMap::ExtendedReinhard { white }),
+            "aces" => Some(ToneMap::Aces),
+            "agx" => Some(ToneMap::Agx),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct PostProcess {
+    pub exposure: f64, // en EV
+    pub tone_map: ToneMap,
+}
+
+impl PostProcess {
+    // Radiance linéaire vers couleur d'affichage linéaire, dans [0, 1]
+    pub fn apply(&self, radiance: Color) -> Color {
+        let c = 2f64.powf(self.exposure) * radiance;
+        let mapped = match self.tone_map {
+            ToneMap::Clamp => c,
+            ToneMap::Reinhard => scale_luminance(c, |l| l / (1.0 + l)),
+            ToneMap::ExtendedReinhard { white } => {
+                scale_luminance(c, |l| l * (1.0 + l / (white * white)) / (1.0 + l))
+            }
+            ToneMap::Aces => aces(c),
+            ToneMap::Agx => agx(c),
+        };
+        Color::new(
+            mapped.x().clamp(0.0, 1.0),
+            mapped.y().clamp(0.0, 1.0),
+            mapped.z().clamp(0.0, 1.0),
+        )
+    }
+}
+
+fn luminance(c: Color) -> f64 {
+    0.2126 * c.x() + 0.7152 * c.y() + 0.0722 * c.z()
+}
+
+// Applique la courbe à la luminance seule, pour garder la teinte
+fn scale_luminance(c: Color, curve: impl Fn(f64) -> f64) -> Color {
+    let l = luminance(c);
+    if l <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    curve(l) / l * c
+}
+
+// Matrice 3x3 donnée par ses colonnes
+fn mul(columns: [[f64; 3]; 3], v: Vec3) -> Vec3 {
+    let column = |i: usize| Vec3::new(columns[i][0], columns[i][1], columns[i][2]);
+    v.x() * column(0) + v.y() * column(1) + v.z() * column(2)
+}
+
+fn aces(c: Color) -> Color {
+    // sRGB -> espace de travail ACES (avec la transformation RRT), puis retour (ODT)
+    const INPUT: [[f64; 3]; 3] = [
+        [0.59719, 0.07600, 0.02840],
+        [0.35458, 0.90834, 0.13383],
+        [0.04823, 0.01566, 0.83777],
+    ];
+    const OUTPUT: [[f64; 3]; 3] = [
+        [1.60475, -0.10208, -0.00327],
+        [-0.53108, 1.10813, -0.07276],
+        [-0.07367, -0.00605, 1.07602],
+    ];
+    let fit = |v: f64| (v * (v + 0.0245786) - 0.000090537) / (v * (0.983729 * v + 0.4329510) + 0.238081);
+    let v = mul(INPUT, c);
+    mul(OUTPUT, Color::new(fit(v.x()), fit(v.y()), fit(v.z())))
+}
+
+fn agx(c: Color) -> Color {
+    // Version "minimale" d'AgX : passage dans un espace plus large, courbe en log2, puis retour
+    const INSET: [[f64; 3]; 3] = [
+        [0.842479062253094, 0.0423282422610123, 0.0423756549057051],
+        [0.0784335999999992, 0.878468636469772, 0.0784336],
+        [0.0792237451477643, 0.0791661274605434, 0.879142973793104],
+    ];
+    const OUTSET: [[f64; 3]; 3] = [
+        [1.19687900512017, -0.0528968517574562, -0.0529716355144438],
+        [-0.0980208811401368, 1.15190312990417, -0.0980434501171241],
+        [-0.0990297440797205, -0.0989611768448433, 1.15107367264116],
+    ];
+    const MIN_EV: f64 = -12.47393;
+    const MAX_EV: f64 = 4.026069;
+
+    let curve = |v: f64| {
+        let x = (v.max(1e-10).log2().clamp(MIN_EV, MAX_EV) - MIN_EV) / (MAX_EV - MIN_EV);
+        // Approximation polynomiale du contraste par défaut
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232
+    };
+    let v = mul(INSET, c);
+    let v = mul(OUTSET, Color::new(curve(v.x()), curve(v.y()), curve(v.z())));
+    // La courbe donne des valeurs déjà encodées pour l'écran : on les relinéarise (gamma 2.2)
+    Color::new(
+        v.x().max(0.0).powf(2.2),
+        v.y().max(0.0).powf(2.2),
+        v.z().max(0.0).powf(2.2),
+    )
+}
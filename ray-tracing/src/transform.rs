@@ -0,0 +1,294 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::common::RenderRng;
+use crate::hittable::{HitRecord, Hittable, Interval};
+use crate::keyframe::{Keyable, Track};
+use crate::matrix::Mat4;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+/*
+Place n'importe quel objet dans la scène avec une matrice 4x4 (translation, rotation, échelle...).
+Le rayon est ramené dans l'espace de l'objet, puis le point et la normale touchés sont renvoyés
+dans l'espace du monde. La direction n'est pas normalisée : t reste donc le même dans les deux espaces.
+L'objet est partagé (Arc) : mille instances d'un même maillage ne coûtent qu'un maillage.
+Le placement peut aussi être animé par des clés : chaque rayon voit l'objet à son propre instant,
+ce qui donne le flou de bougé quand l'obturateur de la caméra reste ouvert.
+*/
+pub struct Transform {
+    object: Arc<dyn Hittable>,
+    pose: Pose,
+    bbox: Option<Aabb>,
+}
+
+// Un placement fixe garde ses matrices calculées une fois pour toutes ; un placement animé
+// n'a que ses clés, interpolées à l'instant de chaque rayon. Les matrices restent dans
+// la structure plutôt que dans une Box : pas d'indirection de plus à chaque rayon
+#[allow(clippy::large_enum_variant)]
+enum Pose {
+    Fixed(Frame),
+    Animated(Track<Placement>),
+}
+
+// Matrices d'un placement, dans les deux sens
+struct Frame {
+    to_world: Mat4,
+    to_object: Mat4,
+    normal_to_world: Mat4, // transposée de l'inverse : garde les normales perpendiculaires aux surfaces
+}
+
+// Placement décomposé, dans l'ordre d'application : échelle, rotations, translation.
+// Contrairement à une matrice, il s'interpole : une rotation de 0 à 360° fait un tour complet
+#[derive(Copy, Clone)]
+pub struct Placement {
+    pub scale: Vec3,
+    pub rotate: Vec3, // angles d'Euler en degrés, autour de X puis Y puis Z
+    pub axis: Vec3,   // rotation de `angle` degrés autour de cet axe, après les angles d'Euler
+    pub angle: f64,
+    pub translate: Vec3,
+}
+
+// Pas de temps par intervalle entre deux clés pour calculer la boîte d'un objet animé
+const MOTION_STEPS: usize = 64;
+
+impl Default for Placement {
+    fn default() -> Placement {
+        Placement {
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            rotate: Vec3::default(),
+            axis: Vec3::new(0.0, 1.0, 0.0),
+            angle: 0.0,
+            translate: Vec3::default(),
+        }
+    }
+}
+
+impl Placement {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::translate(self.translate) * self.rotation() * Mat4::scale(self.scale)
+    }
+
+    // Un axe interpolé entre deux axes opposés peut s'annuler : il n'y a alors plus de rotation
+    fn rotation(&self) -> Mat4 {
+        let euler = Mat4::rotate_euler(self.rotate);
+        if self.axis.near_zero() {
+            euler
+        } else {
+            Mat4::rotate(self.axis, self.angle) * euler
+        }
+    }
+
+    // Matrices dans les deux sens sans inversion générale : la rotation est orthogonale,
+    // donc (T R S)^-1 = S^-1 R^T T^-1. None si une échelle est nulle
+    fn frame(&self) -> Option<Frame> {
+        let s = self.scale;
+        if s.x().abs() < 1e-12 || s.y().abs() < 1e-12 || s.z().abs() < 1e-12 {
+            return None;
+        }
+        let rotation = self.rotation();
+        let to_object = Mat4::scale(Vec3::new(1.0 / s.x(), 1.0 / s.y(), 1.0 / s.z()))
+            * rotation.transpose()
+            * Mat4::translate(-self.translate);
+        Some(Frame {
+            to_world: Mat4::translate(self.translate) * rotation * Mat4::scale(s),
+            to_object,
+            normal_to_world: to_object.transpose(),
+        })
+    }
+}
+
+impl Keyable for Placement {
+    fn weighted(values: &[(f64, Placement)]) -> Placement {
+        let zero = Vec3::default();
+        let mut out = Placement { scale: zero, rotate: zero, axis: zero, angle: 0.0, translate: zero };
+        for &(w, p) in values {
+            out.scale = out.scale + w * p.scale;
+            out.rotate = out.rotate + w * p.rotate;
+            out.axis = out.axis + w * p.axis;
+            out.angle += w * p.angle;
+            out.translate = out.translate + w * p.translate;
+        }
+        out
+    }
+}
+
+impl Frame {
+    // None si la matrice n'est pas inversible (échelle nulle)
+    fn new(to_world: Mat4) -> Option<Frame> {
+        let to_object = to_world.inverse()?;
+        Some(Frame {
+            to_world,
+            to_object,
+            normal_to_world: to_object.transpose(),
+        })
+    }
+
+    fn local_ray(&self, r: &Ray) -> Ray {
+        Ray::new(
+            self.to_object.transform_point(r.origin()),
+            self.to_object.transform_vector(r.direction()),
+            r.time(),
+        )
+    }
+
+    // front_face reste valable : dot(M d, M^-T n) = dot(d, n)
+    fn to_world_record(&self, rec: &mut HitRecord) {
+        rec.p = self.to_world.transform_point(rec.p);
+        rec.normal = Vec3::unit_vector(self.normal_to_world.transform_vector(rec.normal));
+    }
+}
+
+impl Transform {
+    // None si la matrice n'est pas inversible (échelle nulle)
+    pub fn new(object: Arc<dyn Hittable>, to_world: Mat4) -> Option<Transform> {
+        let bbox = object.bounding_box().map(|b| transform_box(&b, &to_world));
+        Some(Transform {
+            pose: Pose::Fixed(Frame::new(to_world)?),
+            object,
+            bbox,
+        })
+    }
+
+    // Placement animé ; None si une clé n'est pas inversible
+    pub fn animated(object: Arc<dyn Hittable>, track: Track<Placement>) -> Option<Transform> {
+        for (_, placement) in track.keys() {
+            placement.frame()?;
+        }
+        // La boîte couvre tout le mouvement : union des boîtes à des instants rapprochés,
+        // élargie de 1 % pour les arcs que décrivent les rotations entre deux instants
+        let bbox = object.bounding_box().map(|b| {
+            let keys = track.keys();
+            let mut times = vec![keys[0].0];
+            for pair in keys.windows(2) {
+                let (t0, t1) = (pair[0].0, pair[1].0);
+                times.extend((1..=MOTION_STEPS).map(|i| t0 + (t1 - t0) * i as f64 / MOTION_STEPS as f64));
+            }
+            let swept = times
+                .iter()
+                .map(|&time| transform_box(&b, &track.at(time).matrix()))
+                .reduce(Aabb::surrounding_box)
+                .unwrap();
+            let margin = 0.01 * (swept.max() - swept.min()).vec_length();
+            let margin = Vec3::new(margin, margin, margin);
+            Aabb::new(swept.min() - margin, swept.max() + margin)
+        });
+        Some(Transform {
+            object,
+            pose: Pose::Animated(track),
+            bbox,
+        })
+    }
+
+    // Intersection calculée par `hit` dans le repère de l'objet, ramenée dans le monde
+    fn hit_local(&self, r: &Ray, rec: &mut HitRecord, hit: impl FnOnce(&Ray, &mut HitRecord) -> bool) -> bool {
+        self.at_time(r.time(), false, |frame| {
+            if !hit(&frame.local_ray(r), rec) {
+                return false;
+            }
+            frame.to_world_record(rec);
+            true
+        })
+    }
+
+    // Appelle `f` avec le placement de l'objet à l'instant `time`,
+    // ou renvoie `missing` si l'échelle interpolée s'annule (l'objet disparaît).
+    // Les matrices d'un placement animé sont reconstruites directement, sans inverser de matrice
+    fn at_time<R>(&self, time: f64, missing: R, f: impl FnOnce(&Frame) -> R) -> R {
+        match &self.pose {
+            Pose::Fixed(frame) => f(frame),
+            Pose::Animated(track) => match track.at(time).frame() {
+                Some(frame) => f(&frame),
+                None => missing,
+            },
+        }
+    }
+}
+
+// Boîte englobant les 8 coins transformés
+fn transform_box(b: &Aabb, m: &Mat4) -> Aabb {
+    let (lo, hi) = (b.min(), b.max());
+    let mut out: Option<Aabb> = None;
+    for i in 0..8 {
+        let corner = Point3::new(
+            if i & 1 == 0 { lo.x() } else { hi.x() },
+            if i & 2 == 0 { lo.y() } else { hi.y() },
+            if i & 4 == 0 { lo.z() } else { hi.z() },
+        );
+        let p = m.transform_point(corner);
+        let pb = Aabb::new(p, p);
+        out = Some(out.map_or(pb, |o| Aabb::surrounding_box(o, pb)));
+    }
+    out.unwrap()
+}
+
+impl Hittable for Transform {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.hit_local(r, rec, |local, rec| self.object.hit(local, t_min, t_max, rec))
+    }
+
+    fn hit_sampled(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord, rng: &mut RenderRng) -> bool {
+        self.hit_local(r, rec, |local, rec| self.object.hit_sampled(local, t_min, t_max, rec, rng))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+
+    fn is_solid(&self) -> bool {
+        self.object.is_solid()
+    }
+
+    fn intervals(&self, r: &Ray) -> Vec<Interval> {
+        self.at_time(r.time(), Vec::new(), |frame| {
+            let mut intervals = self.object.intervals(&frame.local_ray(r));
+            for interval in &mut intervals {
+                frame.to_world_record(&mut interval.enter);
+                frame.to_world_record(&mut interval.exit);
+            }
+            intervals
+        })
+    }
+
+    fn transmittance(&self, r: &Ray, t_min: f64, t_max: f64, rng: &mut RenderRng) -> f64 {
+        self.at_time(r.time(), 1.0, |frame| self.object.transmittance(&frame.local_ray(r), t_min, t_max, rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).vec_length() < 1e-9, "{:?} != {:?}", (a.x(), a.y(), a.z()), (b.x(), b.y(), b.z()));
+    }
+
+    #[test]
+    fn placement_frame_matches_the_general_inverse() {
+        let placement = Placement {
+            scale: Vec3::new(2.0, -0.5, 3.0),
+            rotate: Vec3::new(30.0, -45.0, 110.0),
+            axis: Vec3::new(1.0, 2.0, -0.5),
+            angle: 75.0,
+            translate: Vec3::new(-1.0, 4.0, 0.25),
+        };
+        let frame = placement.frame().unwrap();
+        let reference = Frame::new(placement.matrix()).unwrap();
+        for v in [Vec3::new(1.0, 0.0, 0.0), Vec3::new(-0.3, 2.0, 5.0), Vec3::new(0.7, -1.1, 0.2)] {
+            assert_close(frame.to_world.transform_point(v), reference.to_world.transform_point(v));
+            assert_close(frame.to_object.transform_point(v), reference.to_object.transform_point(v));
+            assert_close(frame.normal_to_world.transform_vector(v), reference.normal_to_world.transform_vector(v));
+            assert_close(frame.to_object.transform_point(frame.to_world.transform_point(v)), v);
+        }
+    }
+
+    #[test]
+    fn degenerate_placements() {
+        let flat = Placement { scale: Vec3::new(1.0, 0.0, 1.0), ..Placement::default() };
+        assert!(flat.frame().is_none());
+        // Axe interpolé à mi-chemin de deux axes opposés : seule la rotation autour de l'axe disparaît
+        let no_axis = Placement { axis: Vec3::default(), angle: 90.0, ..Placement::default() };
+        let frame = no_axis.frame().unwrap();
+        assert_close(frame.to_world.transform_point(Vec3::new(1.0, 2.0, 3.0)), Vec3::new(1.0, 2.0, 3.0));
+    }
+}
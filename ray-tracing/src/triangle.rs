@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+use crate::common::{self, RenderRng};
+use crate::hittable::{HitRecord, Hittable};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+
+// Intersection rayon / triangle de Möller–Trumbore.
+// Renvoie (t, b1, b2) : distance et coordonnées barycentriques des sommets 1 et 2
+pub fn intersect(r: &Ray, p: &[Point3; 3], t_min: f64, t_max: f64) -> Option<(f64, f64, f64)> {
+    let edge1 = p[1] - p[0];
+    let edge2 = p[2] - p[0];
+    let pvec = Vec3::cross(r.direction(), edge2);
+    let det = Vec3::dot(edge1, pvec);
+    // Rayon parallèle au plan du triangle
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let tvec = r.origin() - p[0];
+    let b1 = Vec3::dot(tvec, pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+    let qvec = Vec3::cross(tvec, edge1);
+    let b2 = Vec3::dot(r.direction(), qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = Vec3::dot(edge2, qvec) * inv_det;
+    if t <= t_min || t_max <= t {
+        return None;
+    }
+    Some((t, b1, b2))
+}
+
+// Remplit le HitRecord à partir des barycentriques, en interpolant normales et UV s'ils existent
+#[allow(clippy::too_many_arguments)]
+pub fn fill_record(
+    r: &Ray,
+    rec: &mut HitRecord,
+    (t, b1, b2): (f64, f64, f64),
+    p: &[Point3; 3],
+    normals: Option<&[Vec3; 3]>,
+    uvs: Option<&[[f64; 2]; 3]>,
+    mat: &Arc<dyn Material>,
+) {
+    let b0 = 1.0 - b1 - b2;
+    rec.t = t;
+    rec.p = r.at(t);
+
+    // La normale géométrique décide de la face touchée, la normale interpolée sert à l'éclairage
+    let geometric = Vec3::unit_vector(Vec3::cross(p[1] - p[0], p[2] - p[0]));
+    rec.set_face_normal(r, geometric);
+    if let Some(n) = normals {
+        let shading = Vec3::unit_vector(b0 * n[0] + b1 * n[1] + b2 * n[2]);
+        rec.normal = if Vec3::dot(shading, rec.normal) < 0.0 { -shading } else { shading };
+    }
+
+    match uvs {
+        Some(uv) => {
+            rec.u = b0 * uv[0][0] + b1 * uv[1][0] + b2 * uv[2][0];
+            rec.v = b0 * uv[0][1] + b1 * uv[1][1] + b2 * uv[2][1];
+        }
+        None => {
+            rec.u = b1;
+            rec.v = b2;
+        }
+    }
+    rec.mat = Some(mat.clone());
+}
+
+pub fn bounding_box(p: &[Point3; 3]) -> Aabb {
+    Aabb::surrounding_box(Aabb::new(p[0], p[1]), Aabb::new(p[2], p[2])).pad()
+}
+
+pub struct Triangle {
+    vertices: [Point3; 3],
+    normals: Option<[Vec3; 3]>, // normales par sommet (lissage), sinon normale du plan
+    uvs: Option<[[f64; 2]; 3]>,
+    mat: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(p0: Point3, p1: Point3, p2: Point3, mat: Arc<dyn Material>) -> Triangle {
+        Triangle {
+            vertices: [p0, p1, p2],
+            normals: None,
+            uvs: None,
+            mat,
+        }
+    }
+
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Triangle {
+        self.normals = Some(normals);
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [[f64; 2]; 3]) -> Triangle {
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        match intersect(r, &self.vertices, t_min, t_max) {
+            Some(hit) => {
+                fill_record(r, rec, hit, &self.vertices, self.normals.as_ref(), self.uvs.as_ref(), &self.mat);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(bounding_box(&self.vertices))
+    }
+
+    // Point uniforme sur la surface : densité 1 / aire, convertie en angle solide
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let r = Ray::new(origin, direction, 0.0);
+        let Some((t, _, _)) = intersect(&r, &self.vertices, 0.001, f64::INFINITY) else {
+            return 0.0;
+        };
+        let normal = Vec3::cross(self.vertices[1] - self.vertices[0], self.vertices[2] - self.vertices[0]);
+        let area = 0.5 * normal.vec_length();
+        surface_pdf(direction, t, normal, area)
+    }
+
+    fn random(&self, origin: Point3, rng: &mut RenderRng) -> Vec3 {
+        // La racine carrée évite que les points se concentrent vers le sommet 0
+        let s = common::random_double(rng).sqrt();
+        let b = common::random_double(rng);
+        let p = (1.0 - s) * self.vertices[0] + s * (1.0 - b) * self.vertices[1] + s * b * self.vertices[2];
+        p - origin
+    }
+}
+
+// Densité par angle solide d'un point tiré uniformément sur une surface plane d'aire `area`,
+// touchée au paramètre t du rayon : distance² / (|cos| x aire)
+pub fn surface_pdf(direction: Vec3, t: f64, normal: Vec3, area: f64) -> f64 {
+    let distance_squared = t * t * direction.length_squared();
+    let cosine = (Vec3::dot(direction, normal) / (direction.vec_length() * normal.vec_length())).abs();
+    if cosine < 1e-8 {
+        return 0.0;
+    }
+    distance_squared / (cosine * area)
+}
@@ -1,130 +1,99 @@
-use std::fs::File;
-use std::io::{Result, Write};
-use std::rc::Rc;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
 
-use crate::camera::Camera;
-use crate::color;
-use crate::color::Color;
-use crate::common;
-use crate::constants::*;
-use crate::cube::Cube;
-use crate::cylinder::Cylinder;
-use crate::hittable::{HitRecord, Hittable};
-use crate::hittable_list::HittableList;
-use crate::material::{Lambertian, Metal};
-use crate::plane::Plane;
-use crate::ray::Ray;
-use crate::sphere::Sphere;
-use crate::vec3::{Point3, Vec3};
+use crate::bvh::BvhTree;
+use crate::cli::Options;
+use crate::image;
+use crate::render::{render, RenderSettings};
+use crate::scene::{self, Scene};
 
-pub fn draw_img() -> Result<()> {
-    println!("Image dimensions: {}x{}", IMAGE_WIDTH, IMAGE_HEIGHT);
-
-    // Param fichier
-    let filename = "full_obj_pov1.ppm";
-    let mut file = File::create(filename)?;
-    writeln!(file, "P3\n{} {}\n255", IMAGE_WIDTH, IMAGE_HEIGHT)?;
-
-    // Monde
-    let _r = f64::cos(common::PI / 4.0);
-    let mut world = HittableList::new();
-
-    /*
-        Pour Placer le monde sur une sphere géante:
-        let material_ground = Rc::new(Lambertian::new(Color::new(0.8, 0.8, 0.0)));
-        world.add(Box::new(Sphere::new(Point3::new(0.0, -100.5, -1.0),100.0,material_ground,)));
-    */
-    
-    // surface plane
-    let material_ground = Rc::new(Lambertian::new(Color::new(0.05, 0.05, 0.05)));
-    world.add(Box::new(Plane::new(
-        Point3::new(0.0, 0.0, -1.0), // Un point sur le plan (y = -0.5)
-        Vec3::new(0.0, 5.0, 0.0),    // Normale vers le haut (en direction de l'axe Y)
-        material_ground,
-    )));
-
-    // sphere centered
-    let material_center = Rc::new(Lambertian::new(Color::new(0.1, 0.2, 0.5)));
-    world.add(Box::new(Sphere::new(
-        Point3::new(0.0, 1.0, -2.5),
-        1.0,
-        material_center,
-    )));
-
-    // sphere righted
-    let material_right = Rc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.0));
-    world.add(Box::new(Sphere::new(
-        Point3::new(2.0, 0.7, -1.0),
-        0.7,
-        material_right,
-    )));
-
-    
-    //cube fronted
-    let material_cube = Rc::new(Metal::new(Color::new(0.4, 0.8, 0.6), 0.2));
-    world.add(Box::new(Cube::new(
-        Point3::new(0.0, 0.0, 0.0), // Coin inférieur
-        Point3::new(0.7, 0.7, 0.7), // Coin supérieur
-        material_cube,
-    )));
+fn print_models(scene: &Scene) {
+    for model in &scene.models {
+        println!(
+            "Loaded {}: {} triangles, groups: [{}]",
+            model.file.display(),
+            model.triangles,
+            model.groups.join(", ")
+        );
+    }
+}
 
-    //cylinder on left
-    let material_cylinder = Rc::new(Metal::new(Color::new(0.5, 0.9, 0.3), 0.2));
-    world.add(Box::new(Cylinder::new(
-        Point3::new(-2.0, -1.0, -1.0),
-        2.5, // hauteur
-        0.5, // radius
-        material_cylinder,
-    )));
-    
-    // Camera
-    let cam = Camera::new(
-        Point3::new(0.0, 1.6, 2.0), // ta position
-        Point3::new(0.0, 0.0, -0.5), // point ciblé
-        Vec3::new(0.0, 1.0, 0.0), // indice d'inclinaison - de rotation sur elle-même
-        80.0,
-        ASPECT_RATIO,
-    );
+pub fn draw_img(options: &Options) -> Result<(), Box<dyn Error>> {
+    // Monde, caméra et réglages décrits dans le fichier de scène
+    let scene = scene::load(&options.scene, &options.overrides)?;
+    print_models(&scene);
+    println!("Image dimensions: {}x{}", scene.width, scene.height);
 
     //Render
-    for j in (0..IMAGE_HEIGHT).rev() {
-        eprint!("\rScanlines remaining: {} ", j);
-        for i in 0..IMAGE_WIDTH {
-            let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-            for _ in 0..SAMPLES_PER_PIXEL {
-                let u = (i as f64 + common::random_double()) / (IMAGE_WIDTH - 1) as f64;
-                let v = (j as f64 + common::random_double()) / (IMAGE_HEIGHT - 1) as f64;
-                let r = cam.get_ray(u, v);
-                pixel_color = pixel_color + ray_color(&r, &world, MAX_DEPTH);
-            }
-            color::write_color(&mut file, pixel_color, SAMPLES_PER_PIXEL);
-        }
-    }
-    eprint!("\nDone.\n");
+    let settings = RenderSettings {
+        width: scene.width,
+        height: scene.height,
+        samples_per_pixel: scene.samples_per_pixel,
+        max_depth: scene.max_depth,
+        threads: options.threads,
+        seed: options.seed,
+        background: scene.background,
+        lights: scene.lights,
+        area_lights: scene.area_lights,
+        fog: scene.fog,
+    };
+    eprintln!("Rendering on {} threads, seed {}", settings.threads, settings.seed);
+    let world = BvhTree::new(scene.world);
+    let framebuffer = render(&world, &scene.camera, &settings);
+
+    // Ecriture du framebuffer en une fois
+    image::write_image(&options.output, &framebuffer, options.format, &scene.post)?;
+    println!("Image written to {}", options.output.display());
     Ok(())
 }
 
-fn ray_color(r: &Ray, world: &dyn Hittable, depth: i32) -> Color {
-    // Si nous avons dépassé la profondeur maximale de rebonds, retour à la couleur noire
-    if depth <= 0 {
-        return Color::new(0.0, 0.0, 0.0);
-    }
+// Rendu de l'animation de la scène, image par image, dans le dossier `dir`
+pub fn draw_animation(options: &Options, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let scene = scene::load(&options.scene, &options.overrides)?;
+    print_models(&scene);
+    let animation = scene
+        .animation
+        .ok_or_else(|| format!("{}: no 'animation' statement in the scene", options.scene.display()))?;
+    println!(
+        "Animation: {} frames of {}x{} at {} fps",
+        animation.frames, scene.width, scene.height, animation.fps
+    );
+    fs::create_dir_all(dir)?;
 
-    // Intersection du rayon
-    let mut rec = HitRecord::new();
-    if world.hit(r, 0.001, common::INFINITY, &mut rec) {
-        let mut attenuation = Color::default();
-        let mut scattered = Ray::default();
-        if rec.mat.as_ref().unwrap().scatter(r, &rec, &mut attenuation, &mut scattered) {
-            return attenuation * ray_color(&scattered, world, depth - 1);
+    let mut settings = RenderSettings {
+        width: scene.width,
+        height: scene.height,
+        samples_per_pixel: scene.samples_per_pixel,
+        max_depth: scene.max_depth,
+        threads: options.threads,
+        seed: options.seed,
+        background: scene.background,
+        lights: scene.lights,
+        area_lights: scene.area_lights,
+        fog: scene.fog,
+    };
+    eprintln!("Rendering on {} threads, seed {}", settings.threads, options.seed);
+    // Le monde ne change pas d'une image à l'autre : seul l'instant des rayons avance
+    let world = BvhTree::new(scene.world);
+
+    for frame in 0..animation.frames {
+        let path = dir.join(format!("{:04}.{}", frame + 1, options.format.extension()));
+        // Reprise d'un rendu interrompu : les images déjà écrites sont gardées
+        if path.exists() {
+            println!("Frame {} already rendered, skipped", path.display());
+            continue;
         }
-        return Color::new(0.0, 0.0, 0.0);
-    }
+        // Une graine par image : le bruit change d'une image à l'autre, mais reste reproductible
+        settings.seed = options.seed.wrapping_add(frame as u64);
+        let framebuffer = render(&world, &animation.camera(frame), &settings);
 
-    // Couleur de l'arrière-plan (dégradé)
-    let unit_direction = Vec3::unit_vector(r.direction());
-    let t = 0.3 * (unit_direction.y() + 1.0);
-    
-    // Dégradé du ciel
-    return (2.0 - t) * Color::new(0.4, 0.4, 0.4) + t * Color::new(0.2, 0.4, 0.6);
+        // Écriture sous un nom provisoire puis renommage : une image interrompue
+        // en cours d'écriture n'est jamais prise pour une image terminée
+        let partial = path.with_extension("part");
+        image::write_image(&partial, &framebuffer, options.format, &scene.post)?;
+        fs::rename(&partial, &path)?;
+        println!("Frame written to {}", path.display());
+    }
+    Ok(())
 }
@@ -1,6 +1,6 @@
 use std::fmt::{Display, Formatter, Result};
-use std::ops::{Add, Div, Mul, Neg, Sub};
-use crate::common;
+use std::ops::{Add, Div, Index, Mul, Neg, Sub};
+use crate::common::{self, RenderRng};
 
 #[derive(Copy, Clone, Default)]
 pub struct Vec3 {
@@ -12,19 +12,19 @@ impl Vec3 {
         Vec3 {  axe: [x, y, z] }
     }
 
-    pub fn random() -> Vec3 {
+    pub fn random(rng: &mut RenderRng) -> Vec3 {
         Vec3::new(
-            common::random_double(),
-            common::random_double(),
-            common::random_double(),
+            common::random_double(rng),
+            common::random_double(rng),
+            common::random_double(rng),
         )
     }
  
-    pub fn random_range(min: f64, max: f64) -> Vec3 {
+    pub fn random_range(rng: &mut RenderRng, min: f64, max: f64) -> Vec3 {
         Vec3::new(
-            common::random_double_range(min, max),
-            common::random_double_range(min, max),
-            common::random_double_range(min, max),
+            common::random_double_range(rng, min, max),
+            common::random_double_range(rng, min, max),
+            common::random_double_range(rng, min, max),
         )
     }
 
@@ -61,9 +61,9 @@ impl Vec3 {
         // divise chaque composante du vecteur par lui même pour le normaliser à ~1
     }
 
-    pub fn random_in_unit_sphere() -> Vec3 {
+    pub fn random_in_unit_sphere(rng: &mut RenderRng) -> Vec3 {
         loop {
-            let p = Vec3::random_range(-1.0, 1.0);
+            let p = Vec3::random_range(rng, -1.0, 1.0);
             if p.length_squared() >= 1.0 {
                 continue;
             }
@@ -71,8 +71,18 @@ impl Vec3 {
         }
     }
 
-    pub fn random_unit_vector() -> Vec3 {
-        Self::unit_vector(Self::random_in_unit_sphere())
+    pub fn random_in_unit_disk(rng: &mut RenderRng) -> Vec3 {
+        loop {
+            let p = Vec3::new(
+                common::random_double_range(rng, -1.0, 1.0),
+                common::random_double_range(rng, -1.0, 1.0),
+                0.0,
+            );
+            if p.length_squared() >= 1.0 {
+                continue;
+            }
+            return p;
+        }
     }
 
     pub fn dot(u: Vec3, v: Vec3) -> f64 {
@@ -83,6 +93,14 @@ impl Vec3 {
         v - 2.0 * Vec3::dot(v, n) * n
     }
 
+    pub fn refract(uv: Vec3, n: Vec3, etai_over_etat: f64) -> Vec3 {
+        // Loi de Snell : uv et n unitaires, on sépare la partie perpendiculaire et parallèle à n
+        let cos_theta = f64::min(Vec3::dot(-uv, n), 1.0);
+        let r_out_perp = etai_over_etat * (uv + cos_theta * n);
+        let r_out_parallel = -f64::sqrt(f64::abs(1.0 - r_out_perp.length_squared())) * n;
+        r_out_perp + r_out_parallel
+    }
+
     pub fn cross(u: Vec3, v: Vec3) -> Vec3 {
         Vec3::new(
             u.axe[1] * v.axe[2] - u.axe[2] * v.axe[1],
@@ -111,6 +129,14 @@ impl Display for Vec3 {
     }
 }
 
+impl Index<usize> for Vec3 {
+    type Output = f64;
+    // Accès par numéro d'axe (0 = x, 1 = y, 2 = z)
+    fn index(&self, i: usize) -> &f64 {
+        &self.axe[i]
+    }
+}
+
 impl Neg for Vec3 {
     type Output = Vec3;
     fn neg(self) -> Vec3 {
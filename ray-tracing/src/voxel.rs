@@ -0,0 +1,115 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::vec3::Vec3;
+
+/*
+Grille 3D de densités (nuage, fumée simulée...), lue dans un fichier.
+Chaque valeur est au centre de sa cellule ; entre deux centres la densité est interpolée
+de façon trilinéaire, ce qui évite de voir les cubes de la grille.
+Ordre des valeurs : x varie le plus vite, puis y, puis z.
+Deux formats :
+  - texte : "nx ny nz" puis les nx*ny*nz valeurs, séparées par des blancs ('#' commente la fin de ligne)
+  - brut : flottants 32 bits little-endian, sans en-tête ; les dimensions viennent de la scène
+*/
+pub struct VoxelGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    values: Vec<f64>,
+    max: f64, // plus grande valeur : borne de la densité interpolée
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+impl VoxelGrid {
+    pub fn new([nx, ny, nz]: [usize; 3], values: Vec<f64>) -> io::Result<VoxelGrid> {
+        if nx == 0 || ny == 0 || nz == 0 {
+            return Err(invalid("grid dimensions must not be zero"));
+        }
+        if values.len() != nx * ny * nz {
+            return Err(invalid(&format!(
+                "expected {}x{}x{} = {} values, found {}",
+                nx,
+                ny,
+                nz,
+                nx * ny * nz,
+                values.len()
+            )));
+        }
+        if values.iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err(invalid("densities must be finite and not negative"));
+        }
+        let max = values.iter().fold(0.0, |m: f64, &v| m.max(v));
+        Ok(VoxelGrid { nx, ny, nz, values, max })
+    }
+
+    // Format brut si les dimensions sont données, texte sinon
+    pub fn load(path: &Path, dims: Option<[usize; 3]>) -> io::Result<VoxelGrid> {
+        let bytes = fs::read(path)?;
+        match dims {
+            Some(dims) => {
+                if bytes.len() % 4 != 0 {
+                    return Err(invalid("raw grid size is not a multiple of 4 bytes"));
+                }
+                let values = bytes
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64)
+                    .collect();
+                VoxelGrid::new(dims, values)
+            }
+            None => {
+                let text = String::from_utf8(bytes).map_err(|_| invalid("text grid is not UTF-8, give dims= for a raw grid"))?;
+                VoxelGrid::parse(&text)
+            }
+        }
+    }
+
+    fn parse(text: &str) -> io::Result<VoxelGrid> {
+        let mut words = text
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(|line| line.split_whitespace());
+        let mut dims = [0; 3];
+        for d in &mut dims {
+            let word = words.next().ok_or_else(|| invalid("missing grid dimensions"))?;
+            *d = word.parse().map_err(|_| invalid(&format!("invalid grid dimension '{}'", word)))?;
+        }
+        let values = words
+            .map(|word| word.parse::<f64>().map_err(|_| invalid(&format!("invalid density '{}'", word))))
+            .collect::<io::Result<Vec<f64>>>()?;
+        VoxelGrid::new(dims, values)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    fn at(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.values[(z * self.ny + y) * self.nx + x]
+    }
+
+    // Densité au point `p` de [0, 1]^3 (la grille entière), interpolée entre les 8 centres voisins
+    pub fn sample(&self, p: Vec3) -> f64 {
+        // Indice réel le long d'un axe, bloqué aux centres des cellules du bord
+        let axis = |coord: f64, n: usize| {
+            let x = (coord * n as f64 - 0.5).clamp(0.0, (n - 1) as f64);
+            let i = (x as usize).min(n - 1);
+            (i, (i + 1).min(n - 1), x - i as f64)
+        };
+        let (x0, x1, fx) = axis(p.x(), self.nx);
+        let (y0, y1, fy) = axis(p.y(), self.ny);
+        let (z0, z1, fz) = axis(p.z(), self.nz);
+
+        let lerp = |a: f64, b: f64, f: f64| a + (b - a) * f;
+        let plane = |z| {
+            let bottom = lerp(self.at(x0, y0, z), self.at(x1, y0, z), fx);
+            let top = lerp(self.at(x0, y1, z), self.at(x1, y1, z), fx);
+            lerp(bottom, top, fy)
+        };
+        lerp(plane(z0), plane(z1), fz)
+    }
+}